@@ -2,7 +2,7 @@ use rpi_memory_display::{MemoryDisplay, MemoryDisplayBuffer, Pixel};
 use rppal::spi::{Bus, SlaveSelect};
 
 const WIDTH: usize = 400;
-const HEIGHT: u8 = 240;
+const HEIGHT: usize = 240;
 const CS_GPIO: u8 = 12;
 
 fn main() {
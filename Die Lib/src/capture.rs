@@ -0,0 +1,160 @@
+//! Screenshots: writing a MemoryDisplayBuffer to PBM (P4) or 1-bit
+//! PNG, and reading such files back into a buffer.
+//!
+//! Images are written in the buffer's logical orientation, so a
+//! screenshot of a rotated display looks the way the screen is seen.
+//! PNG support needs the `png` feature.
+
+use crate::raster::Image;
+use crate::{MemoryDisplayBuffer, Pixel};
+use std::io::{self, BufRead, Write};
+
+/// Longest side of an image read back into a buffer. No panel has more
+/// lines than a 10 bit address reaches, even when rotated, so larger
+/// sizes in a header mean a broken file rather than a screenshot.
+const MAX_SIDE: usize = 1024;
+
+impl MemoryDisplayBuffer {
+    /// Returns a copy of the buffer's pixels in logical orientation.
+    pub fn to_image(&self) -> Image {
+        if self.is_native() {
+            return Image::from_bytes(self.width, self.height, self.buffer.clone());
+        }
+        let mut image = Image::new(self.width(), self.height());
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                if self.get_pixel(x, y) == Pixel::Black {
+                    image.set_pixel(x, y, Pixel::Black);
+                }
+            }
+        }
+        image
+    }
+
+    /// Creates a buffer holding `image`, which becomes the native
+    /// orientation of the buffer.
+    ///
+    /// Returns an `InvalidData` error if the image width is not a
+    /// multiple of 8, as the panel rows are whole bytes.
+    pub fn from_image(image: &Image) -> io::Result<Self> {
+        if image.width() % 8 != 0 {
+            return Err(invalid_data("image width must be a multiple of 8"));
+        }
+        let mut buffer = MemoryDisplayBuffer::new(image.width(), image.height());
+        buffer.buffer.copy_from_slice(image.as_ref());
+        Ok(buffer)
+    }
+
+    /// Writes the buffer as a binary PBM (P4) image.
+    pub fn write_pbm<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let image = self.to_image();
+        write!(writer, "P4\n{} {}\n", image.width(), image.height())?;
+        // PBM uses 1 for black.
+        let data: Vec<u8> = image.as_ref().iter().map(|b| !b).collect();
+        writer.write_all(&data)
+    }
+
+    /// Reads a binary PBM (P4) image into a new buffer.
+    ///
+    /// Returns an `InvalidData` error if the image width is not a
+    /// multiple of 8, or either side is longer than a panel's.
+    pub fn read_pbm<R: BufRead>(mut reader: R) -> io::Result<Self> {
+        if pbm_token(&mut reader)? != "P4" {
+            return Err(invalid_data("not a binary PBM (P4) image"));
+        }
+        let width = pbm_number(&mut reader)?;
+        let height = pbm_number(&mut reader)?;
+        // Checked before allocating, as the header can claim any size
+        if width % 8 != 0 {
+            return Err(invalid_data("image width must be a multiple of 8"));
+        }
+        if width > MAX_SIDE || height > MAX_SIDE {
+            return Err(invalid_data("PBM image too large for a panel"));
+        }
+        let len = (width / 8)
+            .checked_mul(height)
+            .ok_or_else(|| invalid_data("PBM image too large for a panel"))?;
+        let mut data = vec![0; len];
+        reader.read_exact(&mut data)?;
+        for byte in &mut data {
+            *byte = !*byte;
+        }
+        MemoryDisplayBuffer::from_image(&Image::from_bytes(width, height, data))
+    }
+
+    /// Writes the buffer as a 1-bit greyscale PNG image.
+    #[cfg(feature = "png")]
+    pub fn write_png<W: Write>(&self, writer: W) -> io::Result<()> {
+        let image = self.to_image();
+        let mut encoder = png::Encoder::new(writer, image.width() as u32, image.height() as u32);
+        encoder.set_color(png::ColorType::Grayscale);
+        encoder.set_depth(png::BitDepth::One);
+        let mut writer = encoder.write_header()?;
+        // 1-bit greyscale uses 1 for white, like the panel.
+        writer.write_image_data(image.as_ref())?;
+        writer.finish()?;
+        Ok(())
+    }
+
+    /// Reads a PNG image into a new buffer. Images that aren't 1-bit
+    /// are thresholded at half brightness; transparent pixels are
+    /// white.
+    #[cfg(feature = "png")]
+    pub fn read_png<R: io::Read>(reader: R) -> io::Result<Self> {
+        let mut decoder = png::Decoder::new(reader);
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+        let mut reader = decoder.read_info()?;
+        let mut data = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut data)?;
+        let (width, height) = (info.width as usize, info.height as usize);
+        let channels = info.color_type.samples();
+        let has_alpha = matches!(info.color_type, png::ColorType::GrayscaleAlpha | png::ColorType::Rgba);
+        let colors = if has_alpha { channels - 1 } else { channels };
+
+        let mut image = Image::new(width, height);
+        for y in 0..height {
+            let row = &data[y * info.line_size..];
+            for x in 0..width {
+                let pixel = &row[x * channels..(x + 1) * channels];
+                let brightness = pixel[..colors].iter().map(|c| *c as usize).sum::<usize>() / colors;
+                let transparent = has_alpha && pixel[colors] < 128;
+                if brightness < 128 && !transparent {
+                    image.set_pixel(x, y, Pixel::Black);
+                }
+            }
+        }
+        MemoryDisplayBuffer::from_image(&image)
+    }
+}
+
+fn invalid_data(reason: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, reason)
+}
+
+/// Reads the next whitespace separated token of a PBM header,
+/// skipping comments. Consumes the single whitespace byte after it.
+fn pbm_token<R: BufRead>(reader: &mut R) -> io::Result<String> {
+    let mut token = String::new();
+    let mut byte = [0];
+    loop {
+        reader.read_exact(&mut byte)?;
+        match byte[0] {
+            b'#' if token.is_empty() => {
+                let mut comment = Vec::new();
+                reader.read_until(b'\n', &mut comment)?;
+            }
+            b if b.is_ascii_whitespace() => {
+                if !token.is_empty() {
+                    return Ok(token);
+                }
+            }
+            b => token.push(b as char),
+        }
+    }
+}
+
+fn pbm_number<R: BufRead>(reader: &mut R) -> io::Result<usize> {
+    pbm_token(reader)?
+        .parse()
+        .map_err(|_| invalid_data("bad PBM image size"))
+}
@@ -0,0 +1,151 @@
+//! Converting greyscale images to 1-bit pixels.
+//!
+//! The panel can only show black or white, so photos and anti-aliased
+//! art need dithering to keep their shading. Sources are 8-bit
+//! greyscale, 0 for black and 255 for white, one byte per pixel in
+//! rows from the top; use `luma` to convert RGB first.
+//!
+//! Error diffusion (Floyd–Steinberg, Atkinson) gives the most detail
+//! for photos. Ordered (Bayer) dithering gives a regular pattern that
+//! doesn't crawl when part of an image changes. A plain threshold
+//! keeps line art crisp.
+
+use crate::raster::Image;
+use crate::Pixel;
+use alloc::vec::Vec;
+
+/// How greyscale is reduced to black and white.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Dither {
+    /// Pixels brighter than the level are white, the rest black.
+    Threshold(u8),
+    /// Error diffusion spreading all of the error to four neighbours.
+    FloydSteinberg,
+    /// Error diffusion spreading 3/4 of the error to six neighbours,
+    /// giving more contrast and cleaner highlights and shadows.
+    Atkinson,
+    /// Ordered dithering with a 4x4 Bayer matrix.
+    Bayer4,
+    /// Ordered dithering with an 8x8 Bayer matrix.
+    Bayer8,
+}
+
+impl Default for Dither {
+    fn default() -> Self {
+        Dither::Threshold(127)
+    }
+}
+
+impl Dither {
+    /// Converts a `width` x `height` greyscale image to pixels, in
+    /// rows from the top.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `gray` is shorter than `width * height`.
+    pub fn apply(self, gray: &[u8], width: usize, height: usize) -> Vec<Pixel> {
+        self.apply_at(gray, width, height, (0, 0))
+    }
+
+    /// Like `apply`, for an image drawn with its top left corner at
+    /// `origin`. Ordered patterns are aligned to the screen rather than
+    /// to the image, so images drawn next to each other (e.g. glyphs)
+    /// continue the same pattern.
+    pub fn apply_at(self, gray: &[u8], width: usize, height: usize, origin: (usize, usize)) -> Vec<Pixel> {
+        let gray = &gray[..width * height];
+        match self {
+            Dither::Threshold(level) => gray.iter().map(|v| pixel(*v > level)).collect(),
+            Dither::FloydSteinberg => diffuse(gray, width, height, 16, FLOYD_STEINBERG),
+            Dither::Atkinson => diffuse(gray, width, height, 8, ATKINSON),
+            Dither::Bayer4 => ordered(gray, width, 2, origin),
+            Dither::Bayer8 => ordered(gray, width, 3, origin),
+        }
+    }
+
+    /// Like `apply`, but returns an Image ready to blit.
+    pub fn apply_to_image(self, gray: &[u8], width: usize, height: usize) -> Image {
+        self.apply_to_image_at(gray, width, height, (0, 0))
+    }
+
+    /// Like `apply_at`, but returns an Image ready to blit at `origin`.
+    pub fn apply_to_image_at(self, gray: &[u8], width: usize, height: usize, origin: (usize, usize)) -> Image {
+        let mut image = Image::new(width, height);
+        for (i, value) in self.apply_at(gray, width, height, origin).into_iter().enumerate() {
+            if value == Pixel::Black {
+                image.set_pixel(i % width, i / width, Pixel::Black);
+            }
+        }
+        image
+    }
+}
+
+/// Returns the perceived brightness of an RGB colour (ITU-R BT.601).
+pub fn luma(r: u8, g: u8, b: u8) -> u8 {
+    ((299 * r as u32 + 587 * g as u32 + 114 * b as u32 + 500) / 1000) as u8
+}
+
+fn pixel(white: bool) -> Pixel {
+    if white {
+        Pixel::White
+    } else {
+        Pixel::Black
+    }
+}
+
+/// Neighbours receiving error as `(dx, dy, weight)`.
+type Kernel = &'static [(isize, usize, i32)];
+
+const FLOYD_STEINBERG: Kernel = &[(1, 0, 7), (-1, 1, 3), (0, 1, 5), (1, 1, 1)];
+const ATKINSON: Kernel = &[(1, 0, 1), (2, 0, 1), (-1, 1, 1), (0, 1, 1), (1, 1, 1), (0, 2, 1)];
+
+/// Error diffusion: each pixel is rounded to black or white and the
+/// difference is spread over its unvisited neighbours, with weights
+/// out of `divisor`.
+fn diffuse(gray: &[u8], width: usize, height: usize, divisor: i32, kernel: Kernel) -> Vec<Pixel> {
+    let mut values: Vec<i32> = gray.iter().map(|v| *v as i32).collect();
+    let mut pixels = Vec::with_capacity(values.len());
+    for y in 0..height {
+        for x in 0..width {
+            let value = values[y * width + x];
+            let white = value > 127;
+            let error = value - if white { 255 } else { 0 };
+            pixels.push(pixel(white));
+            for &(dx, dy, weight) in kernel {
+                let nx = x as isize + dx;
+                let ny = y + dy;
+                if nx >= 0 && (nx as usize) < width && ny < height {
+                    values[ny * width + nx as usize] += error * weight / divisor;
+                }
+            }
+        }
+    }
+    pixels
+}
+
+/// Ordered dithering with a `2^order` square Bayer matrix, tiled from
+/// `origin` up and to the left.
+fn ordered(gray: &[u8], width: usize, order: u32, (x0, y0): (usize, usize)) -> Vec<Pixel> {
+    let cells = 1 << (2 * order);
+    gray.iter()
+        .enumerate()
+        .map(|(i, v)| {
+            let rank = bayer(x0 + i % width, y0 + i / width, order);
+            // Thresholds sit in the middle of each of the `cells`
+            // brightness steps.
+            let level = (2 * rank + 1) * 255 / (2 * cells);
+            pixel(*v as u32 > level)
+        })
+        .collect()
+}
+
+/// Returns the entry of the `2^order` Bayer matrix at `(x, y)`, built
+/// recursively from the 2x2 matrix [[0, 2], [3, 1]].
+fn bayer(x: usize, y: usize, order: u32) -> u32 {
+    let mut rank = 0;
+    for bit in 0..order {
+        let x = (x >> bit) & 1;
+        let y = (y >> bit) & 1;
+        rank = rank * 4 + [[0, 2], [3, 1]][y][x];
+    }
+    rank
+}
@@ -0,0 +1,265 @@
+//! Byte-level encoder for the Memory Display serial protocol.
+//!
+//! The encoder has no hardware dependencies: it turns frames into the
+//! exact byte stream MemoryDisplay writes to SPI, so the protocol can
+//! be inspected and tested without a display attached.
+//!
+//! All bytes are in the order they are clocked out. The command bits
+//! and line addresses are sent LSB first, which is why they appear
+//! bit-reversed compared to the datasheet.
+//!
+//! Panels with 8 bit addresses send a mode byte, then for each line an
+//! address byte, the pixels and a padding byte, and finally one more
+//! padding byte. Panels with 10 bit addresses send a 16 bit header per
+//! line (6 mode bits, only set on the first line, and the address),
+//! the pixels, and two padding bytes at the end.
+
+use crate::panel::{AddressWidth, Panel};
+use crate::{Error, Result};
+use alloc::vec;
+use alloc::vec::Vec;
+
+// In LSB format
+pub const WRITECMD: u8 = 0x80;
+pub const VCOM: u8 = 0x40;
+pub const CLEAR: u8 = 0x20;
+pub const PADDING: u8 = 0;
+
+/// An encoded command ready to be written to the display.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Command {
+    /// The bytes to send while chip select is high.
+    pub bytes: Vec<u8>,
+    /// Rows (0-based) written by this command, in the order they
+    /// appear in `bytes`.
+    pub lines: Vec<usize>,
+}
+
+/// Encodes clear and write commands for a display of a fixed size.
+///
+/// The encoder tracks the VCOM bit, which the display requires to
+/// alternate between commands. Every encoded command uses the current
+/// value and then flips it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Encoder {
+    /// Width of the display in pixels,
+    width: usize,
+    /// Height of the screen in pixels. Must fit in the address width.
+    height: usize,
+    address_width: AddressWidth,
+    vcom: u8,
+}
+
+impl Encoder {
+    /// Creates an encoder for a `width` x `height` display. Displays
+    /// taller than 255 lines use 10 bit line addresses. The first
+    /// command is sent with VCOM high.
+    ///
+    /// Returns `Error::OutOfBounds` if `height` is more than 1023 lines.
+    pub fn new(width: usize, height: usize) -> Result<Self> {
+        Encoder::for_panel(&Panel::new(width, height, 0))
+    }
+
+    /// Creates an encoder for the given panel model.
+    ///
+    /// Returns `Error::OutOfBounds`, naming the last line, if the panel
+    /// is too tall for its address width.
+    pub fn for_panel(panel: &Panel) -> Result<Self> {
+        let max_lines = panel.address_width.max_lines();
+        if panel.height > max_lines {
+            return Err(Error::OutOfBounds {
+                x: 0,
+                y: panel.height - 1,
+                width: panel.width,
+                height: max_lines,
+            });
+        }
+        Ok(Encoder {
+            width: panel.width,
+            height: panel.height,
+            address_width: panel.address_width,
+            vcom: VCOM,
+        })
+    }
+
+    /// Width of the display in pixels.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Height of the display in pixels.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Number of bits used for line addresses.
+    pub fn address_width(&self) -> AddressWidth {
+        self.address_width
+    }
+
+    /// Number of bytes in a single row of pixels.
+    pub fn row_len(&self) -> usize {
+        self.width / 8
+    }
+
+    /// Number of bytes in a full frame.
+    pub fn frame_len(&self) -> usize {
+        self.row_len() * self.height
+    }
+
+    /// Returns true if the next command will be sent with VCOM high.
+    pub fn vcom(&self) -> bool {
+        self.vcom == VCOM
+    }
+
+    /// Encodes a clear command, which sets every pixel to white.
+    pub fn clear(&mut self) -> Command {
+        let mut bytes = Vec::with_capacity(2);
+        self.clear_into(&mut bytes);
+        Command { bytes, lines: vec![] }
+    }
+
+    /// Encodes a write command for every row of `next` that differs
+    /// from `prev`. If there is no previous frame, or it is a
+    /// different size, every row is written.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `next` is shorter than `frame_len()`.
+    pub fn update(&mut self, prev: Option<&[u8]>, next: &[u8]) -> Command {
+        let mut bytes = Vec::new();
+        let mut lines = Vec::new();
+        self.update_into(prev, next, &mut bytes, &mut lines);
+        Command { bytes, lines }
+    }
+
+    /// Encodes a write command for the given `rows` of `next`. A row
+    /// is skipped if it is unchanged from `prev`; pass `None` to write
+    /// every row given.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a row is out of bounds or `next` is shorter than
+    /// `frame_len()`.
+    pub fn write<I>(&mut self, prev: Option<&[u8]>, next: &[u8], rows: I) -> Command
+    where
+        I: IntoIterator<Item = usize>,
+    {
+        let mut bytes = Vec::new();
+        let mut lines = Vec::new();
+        self.write_into(prev, next, rows, &mut bytes, &mut lines);
+        Command { bytes, lines }
+    }
+
+    /// Encodes a command that leaves the pixels alone and only sends
+    /// the VCOM bit. Sent periodically while the screen is static to
+    /// keep VCOM alternating.
+    pub fn toggle_vcom(&mut self) -> Command {
+        let mut bytes = Vec::with_capacity(2);
+        self.toggle_vcom_into(&mut bytes);
+        Command { bytes, lines: vec![] }
+    }
+
+    /// Like `toggle_vcom()`, but appends the command to `out`.
+    pub fn toggle_vcom_into(&mut self, out: &mut Vec<u8>) {
+        out.push(self.vcom);
+        out.push(PADDING);
+        self.vcom ^= VCOM;
+    }
+
+    /// Like `clear()`, but appends the command to `out`.
+    pub fn clear_into(&mut self, out: &mut Vec<u8>) {
+        out.push(self.vcom | CLEAR);
+        out.push(PADDING);
+        self.vcom ^= VCOM;
+    }
+
+    /// Like `update()`, but appends the command to `out` and the
+    /// changed rows to `lines`, so buffers can be reused between
+    /// frames.
+    pub fn update_into(
+        &mut self,
+        prev: Option<&[u8]>,
+        next: &[u8],
+        out: &mut Vec<u8>,
+        lines: &mut Vec<usize>,
+    ) {
+        let height = self.height;
+        self.write_into(prev, next, 0..height, out, lines);
+    }
+
+    /// Like `write()`, but appends the command to `out` and the rows
+    /// written to `lines`.
+    pub fn write_into<I>(
+        &mut self,
+        prev: Option<&[u8]>,
+        next: &[u8],
+        rows: I,
+        out: &mut Vec<u8>,
+        lines: &mut Vec<usize>,
+    ) where
+        I: IntoIterator<Item = usize>,
+    {
+        let row_len = self.row_len();
+        let prev = prev.filter(|prev| prev.len() == next.len());
+        let mode = self.vcom | WRITECMD;
+        let first_line = lines.len();
+        if self.address_width == AddressWidth::Bits8 {
+            out.push(mode);
+        }
+        for y in rows {
+            assert!(y < self.height);
+            let offset = y * row_len;
+            let end = offset + row_len;
+            let row = &next[offset..end];
+            let changed = match prev {
+                Some(prev) => row != &prev[offset..end],
+                None => true,
+            };
+            if changed {
+                match self.address_width {
+                    AddressWidth::Bits8 => {
+                        out.push(line_address(y));
+                        out.extend_from_slice(row);
+                        out.push(PADDING);
+                    }
+                    AddressWidth::Bits10 => {
+                        // Mode bits only go in the first line header.
+                        let bits = if lines.len() == first_line { mode } else { 0 };
+                        out.extend_from_slice(&line_header_10(bits, y));
+                        out.extend_from_slice(row);
+                    }
+                }
+                lines.push(y);
+            }
+        }
+        match self.address_width {
+            AddressWidth::Bits8 => out.push(PADDING),
+            AddressWidth::Bits10 => {
+                if lines.len() == first_line {
+                    // No lines: send the mode bits with a null address.
+                    out.extend_from_slice(&[mode, PADDING]);
+                }
+                out.extend_from_slice(&[PADDING, PADDING]);
+            }
+        }
+        self.vcom ^= VCOM;
+    }
+}
+
+/// Returns the (bit-reversed) address byte for row `y` on a panel with
+/// 8 bit addresses. Display lines are numbered from 1.
+pub fn line_address(y: usize) -> u8 {
+    ((y + 1) as u8).reverse_bits()
+}
+
+/// Returns the 16 bit line header for row `y` on a panel with 10 bit
+/// addresses: the 6 `mode` bits (in the top of the first byte)
+/// followed by the line number LSB first. Display lines are numbered
+/// from 1.
+pub fn line_header_10(mode: u8, y: usize) -> [u8; 2] {
+    let line = (y + 1) as u16;
+    let low = (((line & 1) << 1) | ((line >> 1) & 1)) as u8;
+    let high = ((line >> 2) as u8).reverse_bits();
+    [(mode & 0b11111100) | low, high]
+}
@@ -0,0 +1,238 @@
+//! Sending frames from a background thread.
+//!
+//! At the default 2MHz a full screen takes tens of milliseconds to
+//! send, and `MemoryDisplay::update_buffer` blocks until it is done.
+//! An AsyncDisplay hands frames to a thread that sends them instead:
+//! `present` copies the frame to a back buffer and returns straight
+//! away. When frames are presented faster than they can be sent, only
+//! the latest one is sent, and the rows that changed in the frames
+//! that were skipped are compared along with it.
+
+use crate::vcom::{lock, MaintainedDisplay};
+use crate::{Error, MemoryDisplay, MemoryDisplayBuffer, NoPin, Result};
+use embedded_hal::digital::OutputPin;
+use embedded_hal::spi::SpiBus;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+
+/// A MaintainedDisplay with a thread that sends presented frames.
+///
+/// Errors from the thread are returned by the next `present` or
+/// `wait`. If the thread stops early, e.g. because the bus panicked,
+/// they return `Error::FlushStopped` instead of blocking. Dropping
+/// the AsyncDisplay sends the last presented frame before stopping
+/// the threads.
+pub struct AsyncDisplay<SPI, CS, DISP = NoPin> {
+    // Declared first, so the flush thread stops before VCOM
+    // maintenance does.
+    flusher: Flusher,
+    display: MaintainedDisplay<SPI, CS, DISP>,
+}
+
+struct Flusher {
+    shared: Arc<Shared>,
+    thread: Option<JoinHandle<()>>,
+}
+
+/// State shared between `present` and the flush thread.
+struct Shared {
+    slot: Mutex<Slot>,
+    /// Signalled when a frame is presented, sent, or on shutdown.
+    changed: Condvar,
+}
+
+#[derive(Default)]
+struct Slot {
+    /// The latest presented frame, until the thread takes it.
+    back: Option<MemoryDisplayBuffer>,
+    /// A frame the thread has finished with, reused by `present`.
+    spare: Option<MemoryDisplayBuffer>,
+    /// Number of frames presented.
+    presented: u64,
+    /// Number of presented frames that were sent or skipped.
+    flushed: u64,
+    /// The first error since the last `present` or `wait`.
+    error: Option<Error>,
+    stop: bool,
+    /// Set when the flush thread ends, including by panicking.
+    finished: bool,
+}
+
+impl Slot {
+    /// Takes the error to return from `present` or `wait`.
+    fn take_error(&mut self) -> Result<()> {
+        match self.error.take() {
+            Some(err) => Err(err),
+            None if self.finished && self.flushed < self.presented => Err(Error::FlushStopped),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Marks the flush thread finished when it returns or unwinds, so
+/// `wait` doesn't block on it.
+struct Finished<'a>(&'a Shared);
+
+impl Drop for Finished<'_> {
+    fn drop(&mut self) {
+        lock(&self.0.slot).finished = true;
+        self.0.changed.notify_all();
+    }
+}
+
+impl<SPI, CS, DISP> MaintainedDisplay<SPI, CS, DISP>
+where
+    SPI: SpiBus<u8> + Send + 'static,
+    CS: OutputPin + Send + 'static,
+    DISP: Send + 'static,
+{
+    /// Starts a thread that sends the frames passed to
+    /// `AsyncDisplay::present`, so drawing never waits for SPI.
+    pub fn flush_in_background(self) -> AsyncDisplay<SPI, CS, DISP> {
+        let display = self.shared();
+        let shared = Arc::new(Shared {
+            slot: Mutex::new(Slot::default()),
+            changed: Condvar::new(),
+        });
+        let flushing = shared.clone();
+        let thread = thread::spawn(move || {
+            let _finished = Finished(&flushing);
+            flush(&display, &flushing)
+        });
+        AsyncDisplay {
+            flusher: Flusher {
+                shared,
+                thread: Some(thread),
+            },
+            display: self,
+        }
+    }
+}
+
+impl<SPI, CS, DISP> AsyncDisplay<SPI, CS, DISP> {
+    /// Queues `frame` to be sent and marks it clean. Only its dirty
+    /// rows are compared, as with `MemoryDisplay::update_buffer`. If
+    /// the previous frame hasn't been sent yet, it is replaced.
+    ///
+    /// Frames presented while the display is asleep are skipped, so
+    /// mark the buffer dirty after waking it.
+    pub fn present(&self, frame: &mut MemoryDisplayBuffer) -> Result<()> {
+        let shared = &self.flusher.shared;
+        let mut slot = lock(&shared.slot);
+        match slot.back {
+            // The previous frame's dirty rows haven't been sent, so
+            // they are kept.
+            Some(ref mut back) => back.copy_frame(frame, true),
+            None => {
+                let mut back = slot.spare.take().unwrap_or_else(|| frame.clone());
+                back.copy_frame(frame, false);
+                slot.back = Some(back);
+            }
+        }
+        frame.mark_clean();
+        slot.presented += 1;
+        shared.changed.notify_all();
+        slot.take_error()
+    }
+
+    /// Blocks until every presented frame has been sent, or the flush
+    /// thread has stopped.
+    pub fn wait(&self) -> Result<()> {
+        let shared = &self.flusher.shared;
+        let mut slot = lock(&shared.slot);
+        while slot.flushed < slot.presented && !slot.finished {
+            slot = wait(&shared.changed, slot);
+        }
+        slot.take_error()
+    }
+
+    /// Locks the display, e.g. to put it to sleep. Presented frames
+    /// may still be waiting to be sent; call `wait` first to send
+    /// them.
+    pub fn lock(&self) -> MutexGuard<'_, MemoryDisplay<SPI, CS, DISP>> {
+        self.display.lock()
+    }
+
+    /// Sends any presented frame, stops the flush thread and returns
+    /// the MaintainedDisplay.
+    pub fn into_inner(self) -> MaintainedDisplay<SPI, CS, DISP> {
+        let AsyncDisplay { flusher, display } = self;
+        drop(flusher);
+        display
+    }
+}
+
+impl Drop for Flusher {
+    fn drop(&mut self) {
+        lock(&self.shared.slot).stop = true;
+        self.shared.changed.notify_all();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Runs the flush thread until it is stopped and no frame is left.
+fn flush<SPI, CS, DISP>(display: &Mutex<MemoryDisplay<SPI, CS, DISP>>, shared: &Shared)
+where
+    SPI: SpiBus<u8>,
+    SPI::Error: 'static,
+    CS: OutputPin,
+{
+    let mut slot = lock(&shared.slot);
+    loop {
+        let mut frame = match slot.back.take() {
+            Some(frame) => frame,
+            None if slot.stop => return,
+            None => {
+                slot = wait(&shared.changed, slot);
+                continue;
+            }
+        };
+        let presented = slot.presented;
+        drop(slot);
+
+        let result = {
+            let mut display = lock(display);
+            if display.is_asleep() {
+                Ok(())
+            } else {
+                display.update_buffer(&mut frame).map(drop)
+            }
+        };
+
+        slot = lock(&shared.slot);
+        if let Err(err) = result {
+            slot.error.get_or_insert(err);
+        }
+        slot.flushed = presented;
+        slot.spare = Some(frame);
+        shared.changed.notify_all();
+    }
+}
+
+/// Waits on `changed`, ignoring poisoning like `lock`.
+fn wait<'a>(changed: &Condvar, slot: MutexGuard<'a, Slot>) -> MutexGuard<'a, Slot> {
+    changed.wait(slot).unwrap_or_else(|err| err.into_inner())
+}
+
+impl MemoryDisplayBuffer {
+    /// Copies `frame`'s pixels and layout into this buffer, reusing
+    /// its allocations. With `merge`, rows that are dirty here stay
+    /// dirty.
+    fn copy_frame(&mut self, frame: &MemoryDisplayBuffer, merge: bool) {
+        if merge && self.dirty.len() == frame.dirty.len() {
+            for (dirty, row) in self.dirty.iter_mut().zip(&frame.dirty) {
+                *dirty |= *row;
+            }
+        } else {
+            self.dirty.clone_from(&frame.dirty);
+        }
+        self.buffer.clone_from(&frame.buffer);
+        self.width = frame.width;
+        self.height = frame.height;
+        self.rotation = frame.rotation;
+        self.mirror_x = frame.mirror_x;
+        self.mirror_y = frame.mirror_y;
+    }
+}
@@ -0,0 +1,137 @@
+//! embedded-graphics support for MemoryDisplayBuffer.
+//!
+//! `BinaryColor::On` is ink and is drawn black, `BinaryColor::Off` is
+//! paper and is drawn white. This matches the display's white
+//! background, so mono fonts and images draw as expected.
+
+use crate::{MemoryDisplayBuffer, Pixel};
+use core::convert::Infallible;
+use embedded_graphics_core::draw_target::DrawTarget;
+use embedded_graphics_core::geometry::{Dimensions, OriginDimensions, Size};
+use embedded_graphics_core::pixelcolor::BinaryColor;
+use embedded_graphics_core::primitives::{PointsIter, Rectangle};
+
+impl From<BinaryColor> for Pixel {
+    fn from(color: BinaryColor) -> Self {
+        match color {
+            BinaryColor::On => Pixel::Black,
+            BinaryColor::Off => Pixel::White,
+        }
+    }
+}
+
+impl From<Pixel> for BinaryColor {
+    fn from(pixel: Pixel) -> Self {
+        match pixel {
+            Pixel::Black => BinaryColor::On,
+            Pixel::White => BinaryColor::Off,
+        }
+    }
+}
+
+impl OriginDimensions for MemoryDisplayBuffer {
+    fn size(&self) -> Size {
+        Size::new(self.width() as u32, self.height() as u32)
+    }
+}
+
+impl DrawTarget for MemoryDisplayBuffer {
+    type Color = BinaryColor;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = embedded_graphics_core::Pixel<Self::Color>>,
+    {
+        for embedded_graphics_core::Pixel(point, color) in pixels {
+            if point.x >= 0
+                && point.y >= 0
+                && (point.x as usize) < self.width()
+                && (point.y as usize) < self.height()
+            {
+                self.set_pixel(point.x as usize, point.y as usize, color.into());
+            }
+        }
+        Ok(())
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        let drawable = area.intersection(&self.bounding_box());
+        if drawable.bottom_right().is_none() {
+            return Ok(());
+        }
+        if !self.is_native() {
+            // Rotated or mirrored rows don't map to native rows.
+            return self.draw_iter(
+                area.points()
+                    .zip(colors)
+                    .filter(|(point, _)| drawable.contains(*point))
+                    .map(|(point, color)| embedded_graphics_core::Pixel(point, color)),
+            );
+        }
+        let bytes_per_row = self.width / 8;
+        let mut colors = colors.into_iter();
+        for y in area.rows() {
+            if !drawable.rows().contains(&y) {
+                // Skip this row's colors, keeping the iterator in step
+                // with the area.
+                colors.by_ref().take(area.size.width as usize).for_each(drop);
+                continue;
+            }
+            let row = y as usize * bytes_per_row;
+            // Collect bits into whole bytes before writing them.
+            let mut index = None;
+            let mut bits = 0;
+            let mut mask = 0;
+            for (x, color) in area.columns().zip(colors.by_ref()) {
+                if !drawable.columns().contains(&x) {
+                    continue;
+                }
+                let x = x as usize;
+                let i = row + x / 8;
+                if index != Some(i) {
+                    if let Some(prev) = index {
+                        self.write_bits(prev, bits, mask);
+                    }
+                    index = Some(i);
+                    bits = 0;
+                    mask = 0;
+                }
+                let bit = 0b10000000 >> (x % 8);
+                mask |= bit;
+                if Pixel::from(color) == Pixel::White {
+                    bits |= bit;
+                }
+            }
+            if let Some(i) = index {
+                self.write_bits(i, bits, mask);
+            }
+        }
+        Ok(())
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        let area = area.intersection(&self.bounding_box());
+        if area.bottom_right().is_none() {
+            return Ok(());
+        }
+        let (x, y, width, height) = self.rect_to_native(
+            area.top_left.x as usize,
+            area.top_left.y as usize,
+            area.size.width as usize,
+            area.size.height as usize,
+        );
+        for y in y..y + height {
+            self.fill_span(x, y, width, color.into());
+        }
+        Ok(())
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        self.fill(color.into());
+        Ok(())
+    }
+}
@@ -1,65 +1,187 @@
 //! Library to drive the Sharp Memory Display from a Raspberry Pi.
+//!
+//! The driver is generic over an [`embedded_hal::spi::SpiBus`] and an
+//! [`embedded_hal::digital::OutputPin`] used for chip select, so it
+//! can run on any board with an embedded-hal implementation (or
+//! against an in-memory transport in tests). With the default `rppal`
+//! feature, `MemoryDisplay::new` opens the Raspberry Pi SPI bus and
+//! GPIO pin directly.
+//!
+//! With the `embedded-graphics-core` feature, MemoryDisplayBuffer is
+//! an embedded-graphics `DrawTarget` with `BinaryColor::On` drawn as
+//! black.
+//!
+//! MemoryDisplayBuffer can be saved to and loaded from PBM images, and
+//! PNG images with the `png` feature, for screenshots and reference
+//! frames in tests.
+//!
+//! Without the default `std` feature the crate is `no_std` and only
+//! needs `alloc`: the buffer, diffing, encoder, rasterising and
+//! dithering work as before, but screenshots, background VCOM
+//! maintenance (`MaintainedDisplay`) and the `rppal` and `png`
+//! features need `std`.
 
-use rppal::gpio::{self, Gpio, OutputPin};
-use rppal::spi::{self, Spi, Bus, SlaveSelect};
-use std::fmt;
+#![cfg_attr(not(feature = "std"), no_std)]
 
-// In LSB format
-const WRITECMD: u8 = 0x80;
-const VCOM: u8 = 0x40;
-const CLEAR: u8 = 0x20;
-const PADDING: u8 = 0;
+extern crate alloc;
+
+use alloc::boxed::Box;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::any::Any;
+use core::fmt;
+use core::ops::Range;
+use embedded_hal::digital::{self, OutputPin};
+use embedded_hal::spi::{self, SpiBus};
+
+#[cfg(feature = "std")]
+mod capture;
+pub mod dither;
+pub mod encoder;
+#[cfg(feature = "std")]
+mod flush;
+pub mod panel;
+#[cfg(feature = "embedded-graphics-core")]
+mod graphics;
+mod power;
+mod raster;
+#[cfg(feature = "rppal")]
+mod rpi;
+#[cfg(feature = "std")]
+mod vcom;
+
+pub use power::NoPin;
+pub use raster::Image;
+#[cfg(feature = "std")]
+pub use flush::AsyncDisplay;
+#[cfg(feature = "std")]
+pub use vcom::MaintainedDisplay;
+
+use encoder::Encoder;
+use panel::{AddressWidth, Panel};
+
+#[cfg(feature = "rppal")]
+pub use rpi::{RppalMemoryDisplay, RppalPin, RppalSpi};
 
 #[derive(Debug)]
 pub enum Error {
-    Spi(spi::Error),
-    Gpio(gpio::Error),
+    /// Failed to open or configure the Raspberry Pi SPI device.
+    #[cfg(feature = "rppal")]
+    Spi(rppal::spi::Error),
+    /// Failed to open or configure the Raspberry Pi GPIO pin.
+    #[cfg(feature = "rppal")]
+    Gpio(rppal::gpio::Error),
+    /// The SPI bus reported an error while writing a command.
+    Transport(spi::ErrorKind),
+    /// The chip select pin could not be driven.
+    Pin(digital::ErrorKind),
+    /// A frame passed to the display is not the size of the panel.
+    BufferSize { expected: usize, actual: usize },
+    /// Pixel co-ordinates are outside the buffer.
+    OutOfBounds { x: usize, y: usize, width: usize, height: usize },
+    /// The thread sending frames for an AsyncDisplay stopped, e.g.
+    /// because it panicked, so presented frames won't be sent.
+    #[cfg(feature = "std")]
+    FlushStopped,
 }
 
+#[cfg(feature = "std")]
 impl std::error::Error for Error {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
+            #[cfg(feature = "rppal")]
             Error::Spi(ref err) => Some(err),
+            #[cfg(feature = "rppal")]
             Error::Gpio(ref err) => Some(err),
+            Error::Transport(_) | Error::Pin(_) => None,
+            Error::BufferSize { .. } | Error::OutOfBounds { .. } => None,
+            Error::FlushStopped => None,
         }
     }
 }
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let reason = match self {
-            Error::Spi(ref err) => format!("{}", err),
-            Error::Gpio(ref err) => format!("{}", err),
-        };
-        write!(f, "Memory Display error: {}", reason)
+        write!(f, "Memory Display error: ")?;
+        match self {
+            #[cfg(feature = "rppal")]
+            Error::Spi(ref err) => write!(f, "{}", err),
+            #[cfg(feature = "rppal")]
+            Error::Gpio(ref err) => write!(f, "{}", err),
+            Error::Transport(ref kind) => write!(f, "SPI transfer failed: {}", kind),
+            Error::Pin(ref kind) => write!(f, "chip select failed: {}", kind),
+            Error::BufferSize { expected, actual } => {
+                write!(f, "frame is {} bytes, expected {}", actual, expected)
+            }
+            Error::OutOfBounds { x, y, width, height } => {
+                write!(f, "pixel ({}, {}) is outside the {}x{} buffer", x, y, width, height)
+            }
+            #[cfg(feature = "std")]
+            Error::FlushStopped => write!(f, "the flush thread stopped"),
+        }
+    }
+}
+
+/// Lets SPI buses, such as `RppalSpi`, report an `Error`, which
+/// MemoryDisplay passes on unchanged.
+impl spi::Error for Error {
+    fn kind(&self) -> spi::ErrorKind {
+        match self {
+            Error::Transport(kind) => *kind,
+            _ => spi::ErrorKind::Other,
+        }
     }
 }
 
-impl From<spi::Error> for Error {
-    fn from(err: spi::Error) -> Self {
+#[cfg(feature = "rppal")]
+impl From<rppal::spi::Error> for Error {
+    fn from(err: rppal::spi::Error) -> Self {
         Error::Spi(err)
     }
 }
 
-impl From<gpio::Error> for Error {
-    fn from(err: gpio::Error) -> Self {
+#[cfg(feature = "rppal")]
+impl From<rppal::gpio::Error> for Error {
+    fn from(err: rppal::gpio::Error) -> Self {
         Error::Gpio(err)
     }
 }
 
-pub type Result<T> = std::result::Result<T, Error>;
+pub type Result<T> = core::result::Result<T, Error>;
+
+/// Converts an error of the SPI bus. Buses that already report an
+/// `Error`, like `RppalSpi` with the rppal error as its source, keep
+/// it; for others only the kind of error is known.
+fn transport_error<E: spi::Error + 'static>(err: E) -> Error {
+    let kind = err.kind();
+    let err: Box<dyn Any> = Box::new(err);
+    match err.downcast::<Error>() {
+        Ok(err) => *err,
+        Err(_) => Error::Transport(kind),
+    }
+}
+
+/// What a single update sent to the display.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct UpdateStats {
+    /// Number of lines written.
+    pub lines: usize,
+    /// Number of bytes written over SPI, including the command and
+    /// padding bytes.
+    pub bytes: usize,
+}
 
 /// Sharp Memory Display
+///
+/// `SPI` is the bus the display is attached to and `CS` is the GPIO
+/// pin used as chip select (see the `cs` field for why the bus's own
+/// chip select is not used). `DISP` is the pin driving the panel's
+/// DISP (display on) input, if it is connected (see `with_disp`).
 #[derive(Debug)]
-pub struct MemoryDisplay {
-    /// Width of the display in pixels,
-    width: usize,
-    /// Height of the screen in pixels. Height is u8 because the y
-    /// axis is used for the line address byte (meaning a screen
-    /// cannot exceed 255 pixels high but can be wider).
-    height: u8,
-    vcom: u8,
-    spi: Spi,
+pub struct MemoryDisplay<SPI, CS, DISP = NoPin> {
+    /// Builds the command bytes and tracks the VCOM bit.
+    encoder: Encoder,
+    spi: SPI,
     /// Alternate SlaveSelect pin. SlaveSelect doesn't seem to respect
     /// polarity changes (not sure if rppal lib or hardware) and the
     /// MemoryDisplay uses the non-default ActiveHigh. Instead of
@@ -67,74 +189,101 @@ pub struct MemoryDisplay {
     /// and manually set a separate GPIO pin high/low when writing
     /// data. Memory Display libraries for other languages seem to do
     /// the same.
-    cs: OutputPin,
+    cs: CS,
     /// The previous pixel buffer. Used to diff against new pixel
     /// values so only updated lines are written to the display.
     prev_pixels: Vec<u8>,
+    /// Rows written by the last update.
+    changed_lines: Vec<usize>,
     /// All SPI data is buffered in memory first so it can be sent
     /// efficiently in batches matching the Raspberry Pi spidev.bufsiz
     /// option in boot/cmdline.txt (default settings is 4096).
     cmd_buffer: Vec<u8>,
     /// The value of spidev.bufsiz.
     spidev_bufsiz: usize,
+    /// Number of commands written so far.
+    commands_sent: u64,
+    /// DISP pin. The panel shows its memory while this is high.
+    disp: DISP,
+    /// Set by sleep(), cleared by wake().
+    asleep: bool,
 }
 
-impl MemoryDisplay {
-    /// Initializes a new MemoryDisplay. The `spi_ss` pin should be
-    /// left disconnected (but will still get toggled by the
-    /// hardware). It is replaced by the `cs` GPIO pin.
-    pub fn new(
-        spi_bus: Bus,
-        spi_ss: SlaveSelect,
-        cs_pin: u8,
-        width: usize,
-        height: u8
-    ) -> Result<Self> {
+impl<SPI, CS> MemoryDisplay<SPI, CS>
+where
+    SPI: SpiBus<u8>,
+    CS: OutputPin,
+{
+    /// Initializes a new MemoryDisplay on an already configured SPI
+    /// bus. The bus should run in SPI mode 0 at no more than 2MHz.
+    /// `cs` is driven high for the duration of every command.
+    ///
+    /// Displays taller than 255 lines use 10 bit line addresses. Use
+    /// `with_panel` to pick a specific panel model instead. Returns
+    /// `Error::OutOfBounds` if `height` is more than 1023 lines.
+    pub fn with_spi(spi: SPI, cs: CS, width: usize, height: usize) -> Result<Self> {
+        MemoryDisplay::with_panel(spi, cs, &Panel::new(width, height, 2000000))
+    }
+
+    /// Initializes a new MemoryDisplay for a specific panel model on
+    /// an already configured SPI bus. The bus should run in SPI mode 0
+    /// at no more than `panel.max_clock_hz`. Returns
+    /// `Error::OutOfBounds` if the panel is too tall for its address
+    /// width.
+    pub fn with_panel(spi: SPI, cs: CS, panel: &Panel) -> Result<Self> {
+        let encoder = Encoder::for_panel(panel)?;
         // Required number of bytes to represent all pixels in a row.
-        let row_size = width / 8;
+        let row_size = panel.width / 8;
         // Required number of bytes for each line update: line address
-        // byte, row pixels, trailing padding byte.
-        let line_size = 1 + row_size + 1;
+        // byte, row pixels, trailing padding byte (or the 16 bit
+        // header and row pixels for 10 bit addresses).
+        let line_size = 2 + row_size;
         // Required number of bytes required for a full screen
         // refresh: leading command byte, line updates for
-        // every row, trailing padding byte.
-        let cmd_buffer_size: usize = 1 + (line_size * height as usize) + 1;
+        // every row, trailing padding (two bytes at most).
+        let cmd_buffer_size: usize = 1 + (line_size * panel.height) + 2;
         let cmd_buffer = Vec::with_capacity(cmd_buffer_size);
 
-        let cs = Gpio::new()?.get(cs_pin)?.into_output();
-        let spi = Spi::new(
-            spi_bus,
-            spi_ss,
-            2000000,
-            spi::Mode::Mode0,
-        )?;
-
         Ok(MemoryDisplay {
-            width,
-            height,
-            vcom: VCOM,
+            encoder,
             spi,
             cs,
             cmd_buffer,
             prev_pixels: vec![],
-            spidev_bufsiz: 4096 // (default value on rpi is 4096)
+            changed_lines: Vec::with_capacity(panel.height),
+            spidev_bufsiz: 4096, // (default value on rpi is 4096)
+            commands_sent: 0,
+            disp: NoPin,
+            asleep: false,
         })
     }
+}
+
+impl<SPI, CS, DISP> MemoryDisplay<SPI, CS, DISP>
+where
+    SPI: SpiBus<u8>,
+    SPI::Error: 'static,
+    CS: OutputPin,
+{
+    /// Width of the display in pixels.
+    pub fn width(&self) -> usize {
+        self.encoder.width()
+    }
 
-    /// Sets the SPI clock frequency in Hz.
-    ///
-    /// Default value is 2Mhz (2000000). The spec for the Memory
-    /// Display states a typical clock speed of 1Mhz, and a maximum
-    /// speed of 2Mhz. The screens often do respond at a higher speed
-    /// (e.g. 8MHz) but that will raise the internal temperatures and
-    /// may lower the functional life of your screen.
-    pub fn set_clock_speed(&mut self, clock_hz: u32) -> Result<()> {
-        Ok(self.spi.set_clock_speed(clock_hz)?)
+    /// Height of the display in pixels.
+    pub fn height(&self) -> usize {
+        self.encoder.height()
+    }
+
+    /// Number of bits used for line addresses.
+    pub fn address_width(&self) -> AddressWidth {
+        self.encoder.address_width()
     }
 
-    /// Gets the SPI clock frequency in Hz.
-    pub fn clock_speed(&self) -> Result<u32> {
-        Ok(self.spi.clock_speed()?)
+    /// Consumes the display and returns the SPI bus and chip select
+    /// pin. See `release_disp` to get the DISP pin back.
+    pub fn release(self) -> (SPI, CS) {
+        (self.spi, self.cs)
     }
 
     /// Sets the SPI device buffer size (as configured by the
@@ -144,6 +293,8 @@ impl MemoryDisplay {
     /// cat /sys/module/spidev/parameters/bufsiz
     ///
     /// The default value for the Pi (and this library) is 4096 bytes.
+    /// On other boards this is simply the largest chunk handed to
+    /// `SpiBus::write` at once.
     pub fn set_spidev_bufsiz(&mut self, spidev_bufsiz: usize) {
         self.spidev_bufsiz = spidev_bufsiz;
     }
@@ -155,11 +306,32 @@ impl MemoryDisplay {
         self.spidev_bufsiz
     }
 
+    /// Rows (0-based) that were sent to the display by the last
+    /// update.
+    pub fn changed_lines(&self) -> &[usize] {
+        &self.changed_lines
+    }
+
+    /// Number of commands (clears, updates and VCOM toggles) written
+    /// to the display so far.
+    pub fn commands_sent(&self) -> u64 {
+        self.commands_sent
+    }
+
+    /// Sends a command that only inverts VCOM, leaving the pixels
+    /// unchanged. The datasheet requires VCOM to alternate at least
+    /// once a second or so, even while the screen is static, to avoid
+    /// DC bias and image retention. See `maintain_vcom` to do this
+    /// automatically.
+    pub fn toggle_vcom(&mut self) -> Result<()> {
+        self.encoder.toggle_vcom_into(&mut self.cmd_buffer);
+        self.write_cmd()
+    }
+
     /// Clears the screen.
     pub fn clear(&mut self) -> Result<()> {
-        self.cmd_buffer.push(self.vcom | CLEAR);
-        self.cmd_buffer.push(PADDING);
-        self.prev_pixels = vec![0b11111111; self.width / 8 * self.height as usize];
+        self.encoder.clear_into(&mut self.cmd_buffer);
+        self.prev_pixels = vec![0b11111111; self.encoder.frame_len()];
         self.write_cmd()
     }
 
@@ -169,60 +341,161 @@ impl MemoryDisplay {
     /// The `pixels` buffer can be any [u8] slice where each bit
     /// represents a single black/white pixel (8 pixels per u8).
     /// MemoryDisplayBuffer provides a convenient interface to such a
-    /// buffer.
-    pub fn update<T: AsRef<[u8]>>(&mut self, pixels: T) -> Result<()> {
-        let pixels = pixels.as_ref();
-        let compare = self.prev_pixels.len() == pixels.len();
-        self.cmd_buffer.push(self.vcom | WRITECMD);
-        let mut offset = 0;
-        for y in 0..self.height {
-            let end = offset + self.width / 8;
-            let row = &pixels[offset..end];
-            if !compare || row != &self.prev_pixels[offset..end] {
-                let line_addr = reverse_bits_in_byte(y + 1);
-                self.cmd_buffer.push(line_addr);
-                self.cmd_buffer.extend_from_slice(&row);
-                self.cmd_buffer.push(PADDING);
+    /// buffer. Returns `Error::BufferSize`, without sending anything,
+    /// if `pixels` is not exactly one frame.
+    pub fn update<T: AsRef<[u8]>>(&mut self, pixels: T) -> Result<UpdateStats> {
+        let height = self.encoder.height();
+        self.write_rows(pixels.as_ref(), 0..height, true)
+    }
+
+    /// Updates the display from a MemoryDisplayBuffer, only looking
+    /// at rows the buffer has marked dirty. Dirty rows that still
+    /// match what is on the display are skipped. The buffer is marked
+    /// clean afterwards.
+    ///
+    /// This avoids diffing the whole frame when only a few rows were
+    /// drawn to.
+    pub fn update_buffer(&mut self, buffer: &mut MemoryDisplayBuffer) -> Result<UpdateStats> {
+        let rows = buffer.dirty_rows().collect::<Vec<_>>();
+        let stats = self.write_rows(&buffer.buffer, rows, true)?;
+        buffer.mark_clean();
+        Ok(stats)
+    }
+
+    /// Sends the rows in `rows` to the display, whether they have
+    /// changed or not. Rows outside the display are ignored.
+    pub fn update_rows<T: AsRef<[u8]>>(&mut self, pixels: T, rows: Range<usize>) -> Result<UpdateStats> {
+        let height = self.encoder.height();
+        let rows = rows.start.min(height)..rows.end.min(height);
+        self.write_rows(pixels.as_ref(), rows, false)
+    }
+
+    /// Sends every row to the display, whether it has changed or not.
+    /// Useful to recover from a display that was disturbed (e.g. by a
+    /// loose connection) or to rewrite the panel after power up.
+    pub fn refresh<T: AsRef<[u8]>>(&mut self, pixels: T) -> Result<UpdateStats> {
+        let height = self.encoder.height();
+        self.write_rows(pixels.as_ref(), 0..height, false)
+    }
+
+    /// Encodes and sends a write command for `rows`, skipping rows
+    /// that match the previous frame if `diff` is set.
+    fn write_rows<I>(&mut self, pixels: &[u8], rows: I, diff: bool) -> Result<UpdateStats>
+    where
+        I: IntoIterator<Item = usize>,
+    {
+        let frame_len = self.encoder.frame_len();
+        if pixels.len() != frame_len {
+            return Err(Error::BufferSize { expected: frame_len, actual: pixels.len() });
+        }
+        // The previous frame is only known if a full frame has been
+        // recorded since the last clear.
+        let known = self.prev_pixels.len() == frame_len;
+        let prev = if diff && known { Some(&self.prev_pixels[..]) } else { None };
+        self.changed_lines.clear();
+        self.encoder.write_into(
+            prev,
+            pixels,
+            rows,
+            &mut self.cmd_buffer,
+            &mut self.changed_lines,
+        );
+        let row_len = self.encoder.row_len();
+        if known {
+            for &y in &self.changed_lines {
+                let offset = y * row_len;
+                self.prev_pixels[offset..offset + row_len]
+                    .copy_from_slice(&pixels[offset..offset + row_len]);
             }
-            offset += self.width / 8;
+        } else if self.changed_lines.len() * row_len == frame_len {
+            self.prev_pixels.clear();
+            self.prev_pixels.extend_from_slice(&pixels[..frame_len]);
         }
-        self.cmd_buffer.push(PADDING);
-        self.prev_pixels.copy_from_slice(pixels);
-        self.write_cmd()
+        let stats = UpdateStats {
+            lines: self.changed_lines.len(),
+            bytes: self.cmd_buffer.len(),
+        };
+        self.write_cmd()?;
+        Ok(stats)
     }
 
     fn write_cmd(&mut self) -> Result<()> {
+        let sent = self.send_cmd();
+        // Release chip select and drop the command even if sending
+        // failed, so the next command goes out on its own.
+        let released = self.cs.set_low().map_err(|e| Error::Pin(digital::Error::kind(&e)));
+        self.cmd_buffer.clear();
+        if let Err(err) = sent.and(released) {
+            // The panel may not hold what was recorded for it, so the
+            // next update compares the whole frame again.
+            self.prev_pixels.clear();
+            return Err(err);
+        }
+        self.commands_sent += 1;
+        Ok(())
+    }
+
+    fn send_cmd(&mut self) -> Result<()> {
         // Manually set chip select because the Memory Display uses
         // the less common ActiveHigh polarity and I can't get the
         // hardware SPI device to support it. Other Memory Display
         // libraries do the same thing.
-        self.cs.set_high();
+        self.cs.set_high().map_err(|e| Error::Pin(digital::Error::kind(&e)))?;
         // Write command in chunks matching the spidev_bufsiz for
         // optimal transfer speed.
         for chunk in self.cmd_buffer.chunks(self.spidev_bufsiz) {
-            self.spi.write(chunk)?;
+            self.spi.write(chunk).map_err(transport_error)?;
         }
-        self.cs.set_low();
-        // toggle vcom after every command
-        self.vcom ^= VCOM;
-        // clear command buffer ready for next command
-        self.cmd_buffer.clear();
-        Ok(())
+        // SpiBus::write may return before the last byte is clocked
+        // out, so wait for the bus before releasing chip select.
+        self.spi.flush().map_err(transport_error)
     }
 }
 
-fn reverse_bits_in_byte(byte: u8) -> u8 {
-    let mut tmp: [u8; 1] = [byte];
-    spi::reverse_bits(&mut tmp);
-    tmp[0]
-}
-
 /// A display buffer suitable for use with MemoryDisplay::update().
-#[derive(Debug, Clone, PartialEq)]
+///
+/// The buffer remembers which rows have been drawn to since it was
+/// last marked clean, so MemoryDisplay::update_buffer() only needs to
+/// look at those rows.
+///
+/// Pixels are addressed in logical co-ordinates, which take the
+/// rotation and mirroring into account. The underlying bytes (see
+/// `as_ref()`) are always in the panel's native orientation.
+#[derive(Debug, Clone)]
 pub struct MemoryDisplayBuffer {
+    /// Native width of the panel in pixels.
     width: usize,
-    height: u8,
+    /// Native height of the panel in pixels.
+    height: usize,
     buffer: Vec<u8>,
+    /// One flag per native row, set when any pixel in the row is
+    /// written.
+    dirty: Vec<bool>,
+    rotation: Rotation,
+    mirror_x: bool,
+    mirror_y: bool,
+}
+
+/// Clockwise rotation of the image on the panel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rotation {
+    Deg0,
+    /// Portrait, with the top of the image on the panel's right edge.
+    Deg90,
+    /// Upside down.
+    Deg180,
+    /// Portrait, with the top of the image on the panel's left edge.
+    Deg270,
+}
+
+impl PartialEq for MemoryDisplayBuffer {
+    /// Buffers are equal if they hold the same pixels, regardless of
+    /// which rows are dirty.
+    fn eq(&self, other: &Self) -> bool {
+        self.width == other.width
+            && self.height == other.height
+            && self.buffer == other.buffer
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -233,12 +506,117 @@ pub enum Pixel {
 
 impl MemoryDisplayBuffer {
     /// Creates a new buffer.
-    pub fn new(width: usize, height: u8) -> Self {
+    pub fn new(width: usize, height: usize) -> Self {
         MemoryDisplayBuffer {
             width,
             height,
             // 1 is white, 0 is black
-            buffer: vec![0b11111111; width / 8 * height as usize],
+            buffer: vec![0b11111111; width / 8 * height],
+            // Nothing has been sent yet
+            dirty: vec![true; height],
+            rotation: Rotation::Deg0,
+            mirror_x: false,
+            mirror_y: false,
+        }
+    }
+
+    /// Logical width of the buffer in pixels. This is the panel's
+    /// height when rotated by 90 or 270 degrees.
+    pub fn width(&self) -> usize {
+        match self.rotation {
+            Rotation::Deg0 | Rotation::Deg180 => self.width,
+            Rotation::Deg90 | Rotation::Deg270 => self.height,
+        }
+    }
+
+    /// Logical height of the buffer in pixels. This is the panel's
+    /// width when rotated by 90 or 270 degrees.
+    pub fn height(&self) -> usize {
+        match self.rotation {
+            Rotation::Deg0 | Rotation::Deg180 => self.height,
+            Rotation::Deg90 | Rotation::Deg270 => self.width,
+        }
+    }
+
+    /// Sets how the image is rotated on the panel. Pixels already in
+    /// the buffer are not moved.
+    pub fn set_rotation(&mut self, rotation: Rotation) {
+        self.rotation = rotation;
+    }
+
+    pub fn rotation(&self) -> Rotation {
+        self.rotation
+    }
+
+    /// Mirrors the image left to right (`horizontal`) and/or top to
+    /// bottom (`vertical`). Mirroring is applied before rotation.
+    /// Pixels already in the buffer are not moved.
+    pub fn set_mirror(&mut self, horizontal: bool, vertical: bool) {
+        self.mirror_x = horizontal;
+        self.mirror_y = vertical;
+    }
+
+    /// Returns the (horizontal, vertical) mirroring.
+    pub fn mirror(&self) -> (bool, bool) {
+        (self.mirror_x, self.mirror_y)
+    }
+
+    /// Returns true if logical and native co-ordinates are the same.
+    fn is_native(&self) -> bool {
+        self.rotation == Rotation::Deg0 && !self.mirror_x && !self.mirror_y
+    }
+
+    /// Maps logical co-ordinates to native panel co-ordinates.
+    fn to_native(&self, x: usize, y: usize) -> (usize, usize) {
+        let x = if self.mirror_x { self.width() - 1 - x } else { x };
+        let y = if self.mirror_y { self.height() - 1 - y } else { y };
+        match self.rotation {
+            Rotation::Deg0 => (x, y),
+            Rotation::Deg90 => (self.width - 1 - y, x),
+            Rotation::Deg180 => (self.width - 1 - x, self.height - 1 - y),
+            Rotation::Deg270 => (y, self.height - 1 - x),
+        }
+    }
+
+    /// Maps a non-empty logical rectangle to the native rectangle
+    /// covering the same pixels, as `(x, y, width, height)`.
+    fn rect_to_native(&self, x: usize, y: usize, width: usize, height: usize) -> (usize, usize, usize, usize) {
+        let (ax, ay) = self.to_native(x, y);
+        let (bx, by) = self.to_native(x + width - 1, y + height - 1);
+        let (x0, x1) = (ax.min(bx), ax.max(bx));
+        let (y0, y1) = (ay.min(by), ay.max(by));
+        (x0, y0, x1 - x0 + 1, y1 - y0 + 1)
+    }
+
+    /// Returns the rows that have been written to since the buffer
+    /// was last marked clean, in ascending order.
+    pub fn dirty_rows(&self) -> impl Iterator<Item = usize> + '_ {
+        self.dirty
+            .iter()
+            .enumerate()
+            .filter(|(_, dirty)| **dirty)
+            .map(|(y, _)| y)
+    }
+
+    /// Returns true if row `y` has been written to since the buffer
+    /// was last marked clean.
+    pub fn is_row_dirty(&self, y: usize) -> bool {
+        self.dirty.get(y).copied().unwrap_or(false)
+    }
+
+    /// Marks every row as clean. MemoryDisplay::update_buffer() does
+    /// this after sending the dirty rows.
+    pub fn mark_clean(&mut self) {
+        for dirty in &mut self.dirty {
+            *dirty = false;
+        }
+    }
+
+    /// Marks every row as dirty, so the next update_buffer() compares
+    /// the whole frame.
+    pub fn mark_all_dirty(&mut self) {
+        for dirty in &mut self.dirty {
+            *dirty = true;
         }
     }
 
@@ -248,9 +626,8 @@ impl MemoryDisplayBuffer {
             Pixel::White => 0b11111111,
             Pixel::Black => 0b00000000,
         };
-        // TODO: use self.buffer.fill(b) once fill() API is stablised.
-        // https://github.com/rust-lang/rust/issues/70758
-        self.buffer = vec![b; self.width / 8 * self.height as usize];
+        self.buffer.fill(b);
+        self.mark_all_dirty();
     }
 
     /// Sets the pixel at the given co-ordinates. Set `value` true for
@@ -258,12 +635,15 @@ impl MemoryDisplayBuffer {
     ///
     /// # Panics
     ///
-    /// Panics if `(x, y)` is out of the bounds `(width, height)`.
-    pub fn set_pixel(&mut self, x: usize, y: u8, value: Pixel) {
-        assert!(x < self.width);
-        assert!(y < self.height);
+    /// Panics if `(x, y)` is out of the bounds `(width, height)`. See
+    /// `try_set_pixel` for a version that doesn't.
+    pub fn set_pixel(&mut self, x: usize, y: usize, value: Pixel) {
+        assert!(x < self.width());
+        assert!(y < self.height());
+        let (x, y) = self.to_native(x, y);
         let bytes_per_row = self.width / 8;
-        let index = (y as usize * bytes_per_row) + (x / 8);
+        let index = (y * bytes_per_row) + (x / 8);
+        self.dirty[y] = true;
         let byte = &mut self.buffer[index];
         let bit_offset = x % 8;
         match value {
@@ -292,17 +672,60 @@ impl MemoryDisplayBuffer {
         }
     }
 
+    /// Sets the pixel at the given co-ordinates, or returns
+    /// `Error::OutOfBounds` if they are outside the buffer.
+    pub fn try_set_pixel(&mut self, x: usize, y: usize, value: Pixel) -> Result<()> {
+        self.check_bounds(x, y)?;
+        self.set_pixel(x, y, value);
+        Ok(())
+    }
+
+    /// Returns the pixel at the given co-ordinates, or
+    /// `Error::OutOfBounds` if they are outside the buffer.
+    pub fn try_get_pixel(&self, x: usize, y: usize) -> Result<Pixel> {
+        self.check_bounds(x, y)?;
+        Ok(self.get_pixel(x, y))
+    }
+
+    /// Returns true if `(x, y)` is inside the buffer.
+    pub fn contains(&self, x: usize, y: usize) -> bool {
+        x < self.width() && y < self.height()
+    }
+
+    /// Clips a rectangle, which may start left of or above the buffer,
+    /// to the buffer. Returns the visible part as `(x, y, width,
+    /// height)`, or None if none of it is visible.
+    pub fn clip_rect(&self, x: isize, y: isize, width: usize, height: usize) -> Option<(usize, usize, usize, usize)> {
+        let xs = clip_span(x, width, self.width());
+        let ys = clip_span(y, height, self.height());
+        if xs.is_empty() || ys.is_empty() {
+            None
+        } else {
+            Some((xs.start, ys.start, xs.len(), ys.len()))
+        }
+    }
+
+    fn check_bounds(&self, x: usize, y: usize) -> Result<()> {
+        if self.contains(x, y) {
+            Ok(())
+        } else {
+            Err(Error::OutOfBounds { x, y, width: self.width(), height: self.height() })
+        }
+    }
+
     /// Returns value of pixel at given co-ordinates. Returns true for
     /// white, false for black.
     ///
     /// # Panics
     ///
-    /// Panics if `(x, y)` is out of the bounds `(width, height)`.
-    pub fn get_pixel(&self, x: usize, y: u8) -> Pixel {
-        assert!(x < self.width);
-        assert!(y < self.height);
+    /// Panics if `(x, y)` is out of the bounds `(width, height)`. See
+    /// `try_get_pixel` for a version that doesn't.
+    pub fn get_pixel(&self, x: usize, y: usize) -> Pixel {
+        assert!(x < self.width());
+        assert!(y < self.height());
+        let (x, y) = self.to_native(x, y);
         let bytes_per_row = self.width / 8;
-        let index = (y as usize * bytes_per_row) + (x / 8);
+        let index = (y * bytes_per_row) + (x / 8);
         let byte = &self.buffer[index];
         let bit_offset = x % 8;
         let mask = match bit_offset {
@@ -322,6 +745,53 @@ impl MemoryDisplayBuffer {
             Pixel::Black
         }
     }
+
+    /// Sets `len` pixels starting at native `(x, y)` on a single native
+    /// row, writing whole bytes where possible. The span must be
+    /// within bounds.
+    fn fill_span(&mut self, x: usize, y: usize, len: usize, value: Pixel) {
+        if len == 0 {
+            return;
+        }
+        let b = match value {
+            Pixel::White => 0b11111111,
+            Pixel::Black => 0b00000000,
+        };
+        let row = y * (self.width / 8);
+        self.dirty[y] = true;
+        let last = x + len - 1;
+        let first_byte = row + x / 8;
+        let last_byte = row + last / 8;
+        // Bits from x to the end of its byte, and from the start of
+        // the last byte up to and including `last`.
+        let head = 0b11111111 >> (x % 8);
+        let tail = 0b11111111 << (7 - last % 8);
+        if first_byte == last_byte {
+            self.write_bits(first_byte, b, head & tail);
+        } else {
+            self.write_bits(first_byte, b, head);
+            for byte in &mut self.buffer[first_byte + 1..last_byte] {
+                *byte = b;
+            }
+            self.write_bits(last_byte, b, tail);
+        }
+    }
+
+    /// Replaces the bits selected by `mask` in the byte at `index`.
+    fn write_bits(&mut self, index: usize, bits: u8, mask: u8) {
+        self.dirty[index / (self.width / 8)] = true;
+        let byte = &mut self.buffer[index];
+        *byte = (*byte & !mask) | (bits & mask);
+    }
+}
+
+/// Returns the part of the span of `len` pixels from `pos` that lies
+/// within `0..limit`.
+fn clip_span(pos: isize, len: usize, limit: usize) -> Range<usize> {
+    let limit = limit as isize;
+    let start = pos.clamp(0, limit);
+    let end = pos.saturating_add_unsigned(len).clamp(0, limit);
+    start as usize..end.max(start) as usize
 }
 
 impl AsRef<[u8]> for MemoryDisplayBuffer {
@@ -0,0 +1,84 @@
+//! Sharp Memory Display panel models.
+//!
+//! The panels share one protocol but differ in resolution, in how
+//! many bits are used for the line address and in how fast they can
+//! be clocked. Use one of the presets, or fill in a `Panel` for a
+//! part that isn't listed.
+
+use crate::MemoryDisplayBuffer;
+
+/// Number of bits used to send a line address.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AddressWidth {
+    /// One address byte per line, sent after the mode byte. Used by
+    /// panels up to 255 lines high.
+    Bits8,
+    /// Ten address bits per line, sharing a 16 bit header with the
+    /// mode bits. Used by taller panels such as the LS032B7DD02.
+    Bits10,
+}
+
+impl AddressWidth {
+    /// Returns the address width needed to address `height` lines.
+    pub const fn for_height(height: usize) -> Self {
+        if height <= 255 {
+            AddressWidth::Bits8
+        } else {
+            AddressWidth::Bits10
+        }
+    }
+
+    /// The highest line count this address width can address. Lines
+    /// are numbered from 1.
+    pub fn max_lines(self) -> usize {
+        match self {
+            AddressWidth::Bits8 => 255,
+            AddressWidth::Bits10 => 1023,
+        }
+    }
+}
+
+/// Dimensions and protocol details of a Memory Display panel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Panel {
+    /// Width in pixels. Must be a multiple of 8.
+    pub width: usize,
+    /// Height in pixels.
+    pub height: usize,
+    pub address_width: AddressWidth,
+    /// Maximum SPI clock frequency in Hz listed for the part.
+    pub max_clock_hz: u32,
+}
+
+impl Panel {
+    /// 1.0" 128x128.
+    pub const LS010B7DH04: Panel = Panel::new(128, 128, 1_000_000);
+    /// 1.08" 160x68.
+    pub const LS011B7DH03: Panel = Panel::new(160, 68, 1_000_000);
+    /// 1.18" 184x38.
+    pub const LS012B7DD01: Panel = Panel::new(184, 38, 1_000_000);
+    /// 1.28" 128x128.
+    pub const LS013B7DH03: Panel = Panel::new(128, 128, 1_000_000);
+    /// 1.26" 144x168.
+    pub const LS013B7DH05: Panel = Panel::new(144, 168, 1_000_000);
+    /// 2.7" 400x240, as used by the Adafruit breakout and KramWriter.
+    pub const LS027B7DH01: Panel = Panel::new(400, 240, 2_000_000);
+    /// 3.16" 336x536 with 10 bit line addresses.
+    pub const LS032B7DD02: Panel = Panel::new(336, 536, 2_000_000);
+
+    /// Describes a panel of the given size, choosing the address width
+    /// from the height.
+    pub const fn new(width: usize, height: usize, max_clock_hz: u32) -> Self {
+        Panel {
+            width,
+            height,
+            address_width: AddressWidth::for_height(height),
+            max_clock_hz,
+        }
+    }
+
+    /// Creates a blank buffer matching the panel's resolution.
+    pub fn buffer(&self) -> MemoryDisplayBuffer {
+        MemoryDisplayBuffer::new(self.width, self.height)
+    }
+}
@@ -0,0 +1,110 @@
+//! Turning the panel off and putting it to sleep.
+//!
+//! The panel's DISP input switches the image on and off. While DISP
+//! is low the panel shows white but keeps its memory, so driving it
+//! high again brings the image back without resending it. Without a
+//! DISP pin (`NoPin`), the pin is assumed to be tied high and only
+//! `sleep`'s clear has a visible effect.
+
+use crate::{Error, MemoryDisplay, Result};
+use core::convert::Infallible;
+use embedded_hal::digital::{self, OutputPin};
+use embedded_hal::spi::SpiBus;
+
+/// Stands in for the DISP pin when it is not connected to a GPIO
+/// (e.g. tied high on the breakout board). Setting it does nothing.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NoPin;
+
+impl digital::ErrorType for NoPin {
+    type Error = Infallible;
+}
+
+impl OutputPin for NoPin {
+    fn set_low(&mut self) -> core::result::Result<(), Infallible> {
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> core::result::Result<(), Infallible> {
+        Ok(())
+    }
+}
+
+impl<SPI, CS> MemoryDisplay<SPI, CS>
+where
+    SPI: SpiBus<u8>,
+    SPI::Error: 'static,
+    CS: OutputPin,
+{
+    /// Uses `disp` to drive the panel's DISP pin, and turns the
+    /// display on.
+    pub fn with_disp<P: OutputPin>(self, disp: P) -> Result<MemoryDisplay<SPI, CS, P>> {
+        let mut display = MemoryDisplay {
+            encoder: self.encoder,
+            spi: self.spi,
+            cs: self.cs,
+            prev_pixels: self.prev_pixels,
+            changed_lines: self.changed_lines,
+            cmd_buffer: self.cmd_buffer,
+            spidev_bufsiz: self.spidev_bufsiz,
+            commands_sent: self.commands_sent,
+            disp,
+            asleep: self.asleep,
+        };
+        display.display_on()?;
+        Ok(display)
+    }
+}
+
+impl<SPI, CS, DISP> MemoryDisplay<SPI, CS, DISP>
+where
+    SPI: SpiBus<u8>,
+    SPI::Error: 'static,
+    CS: OutputPin,
+    DISP: OutputPin,
+{
+    /// Shows the image held in the panel's memory.
+    pub fn display_on(&mut self) -> Result<()> {
+        self.disp.set_high().map_err(|e| Error::Pin(digital::Error::kind(&e)))
+    }
+
+    /// Blanks the panel without losing its memory. Updates are still
+    /// written to the panel's memory while it is off.
+    pub fn display_off(&mut self) -> Result<()> {
+        self.disp.set_low().map_err(|e| Error::Pin(digital::Error::kind(&e)))
+    }
+
+    /// Puts the panel in its lowest power state: the screen is
+    /// cleared (so no image is held without VCOM inversion), the
+    /// display is turned off, and `maintain_vcom` stops sending VCOM
+    /// commands until `wake`.
+    ///
+    /// Redraw everything after waking, e.g. with `refresh` or by
+    /// marking the buffer dirty.
+    pub fn sleep(&mut self) -> Result<()> {
+        self.clear()?;
+        self.display_off()?;
+        self.asleep = true;
+        Ok(())
+    }
+
+    /// Turns the display back on after `sleep`.
+    pub fn wake(&mut self) -> Result<()> {
+        self.display_on()?;
+        self.asleep = false;
+        Ok(())
+    }
+}
+
+impl<SPI, CS, DISP> MemoryDisplay<SPI, CS, DISP> {
+    /// Returns true between `sleep` and `wake`.
+    pub fn is_asleep(&self) -> bool {
+        self.asleep
+    }
+
+    /// Consumes the display and returns the SPI bus, chip select pin
+    /// and DISP pin.
+    pub fn release_disp(self) -> (SPI, CS, DISP) {
+        (self.spi, self.cs, self.disp)
+    }
+}
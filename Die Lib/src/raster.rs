@@ -0,0 +1,274 @@
+//! Rectangle fills, spans, inversion and 1-bit image blits.
+//!
+//! All co-ordinates are logical (after rotation and mirroring) and
+//! are clipped to the buffer, so a rectangle or image may hang over
+//! any edge. In the native orientation the operations work on whole
+//! bytes, shifting image rows that don't start on a byte boundary;
+//! otherwise they fall back to setting one pixel at a time.
+
+use crate::{MemoryDisplayBuffer, Pixel};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+/// A 1-bit image in the same format as MemoryDisplayBuffer: rows of
+/// `(width + 7) / 8` bytes, most significant bit leftmost, 1 for
+/// white and 0 for black.
+///
+/// An image may carry a transparency mask in the same format, in
+/// which 1 marks the pixels that are drawn by `blit`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    data: Vec<u8>,
+    mask: Option<Vec<u8>>,
+}
+
+impl Image {
+    /// Creates a white image without a mask.
+    pub fn new(width: usize, height: usize) -> Self {
+        Image::from_bytes(width, height, vec![0b11111111; width.div_ceil(8) * height])
+    }
+
+    /// Creates an image from packed rows.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `data` is not `(width + 7) / 8 * height` bytes long.
+    pub fn from_bytes(width: usize, height: usize, data: Vec<u8>) -> Self {
+        assert_eq!(data.len(), width.div_ceil(8) * height);
+        Image { width, height, data, mask: None }
+    }
+
+    /// Sets the transparency mask, in which 1 marks opaque pixels.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `mask` is not the same length as the image data.
+    pub fn with_mask(mut self, mask: Vec<u8>) -> Self {
+        assert_eq!(mask.len(), self.data.len());
+        self.mask = Some(mask);
+        self
+    }
+
+    /// Sets a mask that makes every pixel of colour `value`
+    /// transparent, e.g. `Pixel::White` for black ink on paper.
+    pub fn with_transparent(self, value: Pixel) -> Self {
+        let mask = match value {
+            Pixel::White => self.data.iter().map(|b| !b).collect(),
+            Pixel::Black => self.data.clone(),
+        };
+        self.with_mask(mask)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns the transparency mask, if any.
+    pub fn mask(&self) -> Option<&[u8]> {
+        self.mask.as_deref()
+    }
+
+    /// Sets the pixel at the given co-ordinates.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `(x, y)` is out of the bounds `(width, height)`.
+    pub fn set_pixel(&mut self, x: usize, y: usize, value: Pixel) {
+        assert!(x < self.width && y < self.height);
+        let index = y * self.stride() + x / 8;
+        let bit = 0b10000000 >> (x % 8);
+        match value {
+            Pixel::White => self.data[index] |= bit,
+            Pixel::Black => self.data[index] &= !bit,
+        }
+    }
+
+    /// Returns the pixel at the given co-ordinates.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `(x, y)` is out of the bounds `(width, height)`.
+    pub fn get_pixel(&self, x: usize, y: usize) -> Pixel {
+        assert!(x < self.width && y < self.height);
+        if bit(&self.data[y * self.stride()..], x as isize) {
+            Pixel::White
+        } else {
+            Pixel::Black
+        }
+    }
+
+    fn stride(&self) -> usize {
+        self.width.div_ceil(8)
+    }
+
+    fn row(&self, y: usize) -> &[u8] {
+        &self.data[y * self.stride()..(y + 1) * self.stride()]
+    }
+
+    fn mask_row(&self, y: usize) -> Option<&[u8]> {
+        let stride = self.stride();
+        self.mask.as_ref().map(|mask| &mask[y * stride..(y + 1) * stride])
+    }
+
+    fn is_opaque(&self, x: usize, y: usize) -> bool {
+        match self.mask_row(y) {
+            Some(mask) => bit(mask, x as isize),
+            None => true,
+        }
+    }
+}
+
+impl AsRef<[u8]> for Image {
+    fn as_ref(&self) -> &[u8] {
+        &self.data[..]
+    }
+}
+
+/// How the pixels of an image are written to the buffer.
+#[derive(Clone, Copy)]
+enum Ink {
+    /// Copy the image, skipping pixels outside its mask.
+    Copy,
+    /// Set the image's black pixels to the given value.
+    Stamp(Pixel),
+}
+
+impl MemoryDisplayBuffer {
+    /// Sets every pixel in the rectangle to `value`.
+    pub fn fill_rect(&mut self, x: usize, y: usize, width: usize, height: usize, value: Pixel) {
+        if let Some((x, y, width, height)) = self.clip_rect(x as isize, y as isize, width, height) {
+            let (x, y, width, height) = self.rect_to_native(x, y, width, height);
+            for y in y..y + height {
+                self.fill_span(x, y, width, value);
+            }
+        }
+    }
+
+    /// Sets `len` pixels to the right of and including `(x, y)`.
+    pub fn hspan(&mut self, x: usize, y: usize, len: usize, value: Pixel) {
+        self.fill_rect(x, y, len, 1, value);
+    }
+
+    /// Sets `len` pixels below and including `(x, y)`.
+    pub fn vspan(&mut self, x: usize, y: usize, len: usize, value: Pixel) {
+        self.fill_rect(x, y, 1, len, value);
+    }
+
+    /// Swaps black and white for every pixel in the rectangle.
+    pub fn invert_rect(&mut self, x: usize, y: usize, width: usize, height: usize) {
+        if let Some((x, y, width, height)) = self.clip_rect(x as isize, y as isize, width, height) {
+            let (x, y, width, height) = self.rect_to_native(x, y, width, height);
+            let bytes_per_row = self.width / 8;
+            for y in y..y + height {
+                self.dirty[y] = true;
+                let row = y * bytes_per_row;
+                for (i, mask) in span_masks(x..x + width) {
+                    self.buffer[row + i] ^= mask;
+                }
+            }
+        }
+    }
+
+    /// Copies `image` with its top left corner at `(x, y)`. Pixels
+    /// outside the image's mask are left unchanged.
+    pub fn blit(&mut self, x: isize, y: isize, image: &Image) {
+        self.draw_image(x, y, image, Ink::Copy);
+    }
+
+    /// Sets the pixels under the black pixels of `image` to `value`,
+    /// leaving the rest unchanged. This draws a black-on-white icon or
+    /// glyph in either colour. The image's mask is ignored.
+    pub fn stamp(&mut self, x: isize, y: isize, image: &Image, value: Pixel) {
+        self.draw_image(x, y, image, Ink::Stamp(value));
+    }
+
+    fn draw_image(&mut self, x: isize, y: isize, image: &Image, ink: Ink) {
+        // The visible part of the image, in image co-ordinates.
+        let cols = visible(x, image.width, self.width());
+        let rows = visible(y, image.height, self.height());
+        if cols.is_empty() || rows.is_empty() {
+            return;
+        }
+
+        if !self.is_native() {
+            for iy in rows {
+                for ix in cols.clone() {
+                    let value = match ink {
+                        Ink::Copy if image.is_opaque(ix, iy) => image.get_pixel(ix, iy),
+                        Ink::Stamp(value) if image.get_pixel(ix, iy) == Pixel::Black => value,
+                        _ => continue,
+                    };
+                    let bx = (x + ix as isize) as usize;
+                    let by = (y + iy as isize) as usize;
+                    self.set_pixel(bx, by, value);
+                }
+            }
+            return;
+        }
+
+        let bytes_per_row = self.width / 8;
+        let start = (x + cols.start as isize) as usize;
+        let end = (x + cols.end as isize) as usize;
+        for iy in rows {
+            let src = image.row(iy);
+            let mask = image.mask_row(iy);
+            let by = (y + iy as isize) as usize;
+            let row = by * bytes_per_row;
+            for (i, cols) in span_masks(start..end) {
+                // Image bit offset of the first pixel in this byte.
+                let offset = (i * 8) as isize - x;
+                let (bits, mask) = match ink {
+                    Ink::Copy => (byte_at(src, offset), mask.map_or(0b11111111, |m| byte_at(m, offset))),
+                    Ink::Stamp(Pixel::White) => (0b11111111, !byte_at(src, offset)),
+                    Ink::Stamp(Pixel::Black) => (0b00000000, !byte_at(src, offset)),
+                };
+                self.write_bits(row + i, bits, mask & cols);
+            }
+        }
+    }
+}
+
+/// Returns the range of an image's `len` pixels, placed at `pos`, that
+/// fall within `0..limit`.
+fn visible(pos: isize, len: usize, limit: usize) -> Range<usize> {
+    let start = (-pos).max(0) as usize;
+    let end = (limit as isize - pos).clamp(0, len as isize) as usize;
+    start.min(end)..end
+}
+
+/// Splits the pixel range `cols` of a row into the index of each byte
+/// it touches and a mask of the bits it covers in that byte.
+fn span_masks(cols: Range<usize>) -> impl Iterator<Item = (usize, u8)> {
+    let first = cols.start / 8;
+    let last = (cols.end - 1) / 8;
+    (first..=last).map(move |i| {
+        let head = if i == first { 0b11111111 >> (cols.start % 8) } else { 0b11111111 };
+        let tail = if i == last { 0b11111111 << (7 - (cols.end - 1) % 8) } else { 0b11111111 };
+        (i, head & tail)
+    })
+}
+
+/// Returns the 8 bits of `row` starting at bit `offset`, which need
+/// not be a multiple of 8. Bits outside the row are 0.
+fn byte_at(row: &[u8], offset: isize) -> u8 {
+    let get = |i: isize| if i >= 0 && (i as usize) < row.len() { row[i as usize] } else { 0 };
+    let index = offset.div_euclid(8);
+    let shift = offset.rem_euclid(8) as u32;
+    if shift == 0 {
+        get(index)
+    } else {
+        (get(index) << shift) | (get(index + 1) >> (8 - shift))
+    }
+}
+
+/// Returns true if bit `offset` of `row` is set.
+fn bit(row: &[u8], offset: isize) -> bool {
+    byte_at(row, offset) & 0b10000000 != 0
+}
@@ -0,0 +1,160 @@
+//! Raspberry Pi transport built on rppal.
+//!
+//! rppal 0.11 does not implement the embedded-hal 1.0 traits, so the
+//! SPI device and GPIO pin are wrapped in thin adapters that do.
+
+use crate::panel::Panel;
+use crate::{Error, MemoryDisplay, Result};
+use core::convert::Infallible;
+use embedded_hal::digital::{self, OutputPin};
+use embedded_hal::spi::{self, SpiBus};
+use rppal::gpio::{self, Gpio};
+use rppal::spi::{Bus, SlaveSelect, Spi};
+
+/// A MemoryDisplay driven through the Raspberry Pi SPI and GPIO
+/// peripherals.
+pub type RppalMemoryDisplay = MemoryDisplay<RppalSpi, RppalPin>;
+
+/// rppal SPI device implementing `embedded_hal::spi::SpiBus`.
+#[derive(Debug)]
+pub struct RppalSpi(Spi);
+
+/// rppal GPIO output pin implementing
+/// `embedded_hal::digital::OutputPin`.
+#[derive(Debug)]
+pub struct RppalPin(gpio::OutputPin);
+
+impl RppalSpi {
+    pub fn new(spi: Spi) -> Self {
+        RppalSpi(spi)
+    }
+
+    /// Returns the wrapped rppal SPI device.
+    pub fn into_inner(self) -> Spi {
+        self.0
+    }
+}
+
+impl RppalPin {
+    pub fn new(pin: gpio::OutputPin) -> Self {
+        RppalPin(pin)
+    }
+
+    /// Opens the GPIO pin with BCM number `pin` as an output, e.g. for
+    /// the EXTCOMIN pin passed to `maintain_vcom_extcomin` or the DISP
+    /// pin passed to `with_disp`.
+    pub fn open(pin: u8) -> Result<Self> {
+        Ok(RppalPin(Gpio::new()?.get(pin)?.into_output()))
+    }
+
+    /// Returns the wrapped rppal output pin.
+    pub fn into_inner(self) -> gpio::OutputPin {
+        self.0
+    }
+}
+
+impl spi::ErrorType for RppalSpi {
+    type Error = Error;
+}
+
+impl SpiBus<u8> for RppalSpi {
+    fn read(&mut self, words: &mut [u8]) -> Result<()> {
+        self.0.read(words)?;
+        Ok(())
+    }
+
+    fn write(&mut self, words: &[u8]) -> Result<()> {
+        self.0.write(words)?;
+        Ok(())
+    }
+
+    fn transfer(&mut self, read: &mut [u8], write: &[u8]) -> Result<()> {
+        self.0.transfer(read, write)?;
+        Ok(())
+    }
+
+    fn transfer_in_place(&mut self, words: &mut [u8]) -> Result<()> {
+        let write = words.to_vec();
+        self.0.transfer(words, &write)?;
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        // spidev transfers are synchronous.
+        Ok(())
+    }
+}
+
+impl digital::ErrorType for RppalPin {
+    type Error = Infallible;
+}
+
+impl OutputPin for RppalPin {
+    fn set_low(&mut self) -> core::result::Result<(), Infallible> {
+        self.0.set_low();
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> core::result::Result<(), Infallible> {
+        self.0.set_high();
+        Ok(())
+    }
+}
+
+impl MemoryDisplay<RppalSpi, RppalPin> {
+    /// Initializes a new MemoryDisplay. The `spi_ss` pin should be
+    /// left disconnected (but will still get toggled by the
+    /// hardware). It is replaced by the `cs` GPIO pin.
+    pub fn new(
+        spi_bus: Bus,
+        spi_ss: SlaveSelect,
+        cs_pin: u8,
+        width: usize,
+        height: usize
+    ) -> Result<Self> {
+        MemoryDisplay::new_for_panel(
+            spi_bus,
+            spi_ss,
+            cs_pin,
+            &Panel::new(width, height, 2000000),
+        )
+    }
+
+    /// Initializes a new MemoryDisplay for a specific panel model,
+    /// with the SPI clock set to the panel's maximum (capped at the
+    /// 2MHz default).
+    pub fn new_for_panel(
+        spi_bus: Bus,
+        spi_ss: SlaveSelect,
+        cs_pin: u8,
+        panel: &Panel
+    ) -> Result<Self> {
+        let cs = RppalPin::open(cs_pin)?;
+        let spi = Spi::new(
+            spi_bus,
+            spi_ss,
+            panel.max_clock_hz.min(2000000),
+            rppal::spi::Mode::Mode0,
+        )?;
+
+        MemoryDisplay::with_panel(RppalSpi(spi), cs, panel)
+    }
+}
+
+impl<DISP> MemoryDisplay<RppalSpi, RppalPin, DISP> {
+    /// Sets the SPI clock frequency in Hz.
+    ///
+    /// Default value is 2Mhz (2000000). The spec for the Memory
+    /// Display states a typical clock speed of 1Mhz, and a maximum
+    /// speed of 2Mhz. The screens often do respond at a higher speed
+    /// (e.g. 8MHz) but that will raise the internal temperatures and
+    /// may lower the functional life of your screen.
+    pub fn set_clock_speed(&mut self, clock_hz: u32) -> Result<()> {
+        Ok(self.spi.0.set_clock_speed(clock_hz)?)
+    }
+
+    /// Gets the SPI clock frequency in Hz.
+    pub fn clock_speed(&self) -> Result<u32> {
+        Ok(self.spi.0.clock_speed()?)
+    }
+}
@@ -0,0 +1,135 @@
+//! Keeping VCOM alternating while the screen is static.
+//!
+//! The panel must see VCOM invert regularly (the datasheets ask for
+//! 1Hz or more) or the liquid crystal builds up a DC bias, which leads
+//! to image retention. Every command sent by MemoryDisplay inverts
+//! VCOM, but a screen that isn't updated for minutes gets no
+//! inversions at all. A MaintainedDisplay runs a background thread
+//! that takes care of this, either by sending VCOM-only commands
+//! (software mode) or by toggling the panel's EXTCOMIN pin (hardware
+//! mode, with the panel's EXTMODE pin tied high).
+
+use crate::{MemoryDisplay, NoPin};
+use embedded_hal::digital::OutputPin;
+use embedded_hal::spi::SpiBus;
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// A MemoryDisplay shared with a thread that keeps VCOM alternating.
+///
+/// Use `lock()` to draw to the display. The thread stops when the
+/// MaintainedDisplay is dropped.
+pub struct MaintainedDisplay<SPI, CS, DISP = NoPin> {
+    display: Arc<Mutex<MemoryDisplay<SPI, CS, DISP>>>,
+    /// Dropping the sender wakes the thread and tells it to exit.
+    stop: Option<Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl<SPI, CS, DISP> MemoryDisplay<SPI, CS, DISP>
+where
+    SPI: SpiBus<u8> + Send + 'static,
+    CS: OutputPin + Send + 'static,
+    DISP: Send + 'static,
+{
+    /// Moves the display to a MaintainedDisplay that sends a VCOM-only
+    /// command whenever nothing else has been sent to the display for
+    /// `period`. Nothing is sent while the display is asleep.
+    ///
+    /// Errors from the background commands are ignored; the next
+    /// update will report a broken connection.
+    pub fn maintain_vcom(self, period: Duration) -> MaintainedDisplay<SPI, CS, DISP> {
+        let display = Arc::new(Mutex::new(self));
+        let shared = display.clone();
+        let (stop, stopped) = mpsc::channel::<()>();
+        let thread = thread::spawn(move || {
+            let mut seen = lock(&shared).commands_sent();
+            while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(period) {
+                let mut display = lock(&shared);
+                if display.commands_sent() == seen && !display.is_asleep() {
+                    let _ = display.toggle_vcom();
+                }
+                seen = display.commands_sent();
+            }
+        });
+        MaintainedDisplay {
+            display,
+            stop: Some(stop),
+            thread: Some(thread),
+        }
+    }
+
+    /// Moves the display to a MaintainedDisplay that inverts VCOM by
+    /// toggling the panel's EXTCOMIN pin, giving one rising edge every
+    /// `period`. The panel's EXTMODE pin must be tied high, in which
+    /// case the VCOM bit in commands is ignored by the panel.
+    ///
+    /// The pin is held low while the display is asleep and when the
+    /// thread stops.
+    pub fn maintain_vcom_extcomin<P>(self, mut extcomin: P, period: Duration) -> MaintainedDisplay<SPI, CS, DISP>
+    where
+        P: OutputPin + Send + 'static,
+    {
+        let display = Arc::new(Mutex::new(self));
+        let shared = display.clone();
+        let (stop, stopped) = mpsc::channel::<()>();
+        let thread = thread::spawn(move || {
+            let mut high = false;
+            while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(period / 2) {
+                high = !high && !lock(&shared).is_asleep();
+                let _ = if high { extcomin.set_high() } else { extcomin.set_low() };
+            }
+            let _ = extcomin.set_low();
+        });
+        MaintainedDisplay {
+            display,
+            stop: Some(stop),
+            thread: Some(thread),
+        }
+    }
+}
+
+impl<SPI, CS, DISP> MaintainedDisplay<SPI, CS, DISP> {
+    /// Locks the display for drawing. The background thread waits
+    /// while the display is locked.
+    pub fn lock(&self) -> MutexGuard<'_, MemoryDisplay<SPI, CS, DISP>> {
+        lock(&self.display)
+    }
+
+    /// Returns the shared display, for other background threads.
+    pub(crate) fn shared(&self) -> Arc<Mutex<MemoryDisplay<SPI, CS, DISP>>> {
+        self.display.clone()
+    }
+
+    /// Stops the background thread and returns the display.
+    pub fn into_inner(mut self) -> MemoryDisplay<SPI, CS, DISP> {
+        self.shutdown();
+        let display = self.display.clone();
+        drop(self);
+        match Arc::try_unwrap(display) {
+            Ok(display) => display.into_inner().unwrap_or_else(|err| err.into_inner()),
+            Err(_) => unreachable!("background thread has exited"),
+        }
+    }
+
+    fn shutdown(&mut self) {
+        self.stop.take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl<SPI, CS, DISP> Drop for MaintainedDisplay<SPI, CS, DISP> {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+/// Locks the display, ignoring poisoning: a panic while drawing
+/// leaves the display usable.
+pub(crate) fn lock<T>(display: &Mutex<T>) -> MutexGuard<'_, T> {
+    display.lock().unwrap_or_else(|err| err.into_inner())
+}
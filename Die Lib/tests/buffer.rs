@@ -0,0 +1,186 @@
+//! MemoryDisplayBuffer pixel addressing and raster operations.
+
+use rpi_memory_display::{Error, Image, MemoryDisplayBuffer, Pixel, Rotation};
+
+const WIDTH: usize = 16;
+const HEIGHT: usize = 8;
+
+/// Returns the native co-ordinates of the single black pixel.
+fn black_pixel(buffer: &MemoryDisplayBuffer) -> (usize, usize) {
+    let bytes = buffer.as_ref();
+    let index = bytes.iter().position(|b| *b != 0xff).unwrap();
+    let bit = bytes[index].leading_ones() as usize;
+    ((index % (WIDTH / 8)) * 8 + bit, index / (WIDTH / 8))
+}
+
+fn draw(rotation: Rotation, mirror: (bool, bool), x: usize, y: usize) -> (usize, usize) {
+    let mut buffer = MemoryDisplayBuffer::new(WIDTH, HEIGHT);
+    buffer.set_rotation(rotation);
+    buffer.set_mirror(mirror.0, mirror.1);
+    buffer.set_pixel(x, y, Pixel::Black);
+    assert_eq!(buffer.get_pixel(x, y), Pixel::Black);
+    black_pixel(&buffer)
+}
+
+#[test]
+fn rotation_maps_to_native_pixels() {
+    assert_eq!(draw(Rotation::Deg0, (false, false), 1, 2), (1, 2));
+    assert_eq!(draw(Rotation::Deg90, (false, false), 1, 2), (13, 1));
+    assert_eq!(draw(Rotation::Deg180, (false, false), 1, 2), (14, 5));
+    assert_eq!(draw(Rotation::Deg270, (false, false), 1, 2), (2, 6));
+}
+
+#[test]
+fn mirroring_maps_to_native_pixels() {
+    assert_eq!(draw(Rotation::Deg0, (true, false), 1, 2), (14, 2));
+    assert_eq!(draw(Rotation::Deg0, (false, true), 1, 2), (1, 5));
+    assert_eq!(draw(Rotation::Deg0, (true, true), 1, 2), (14, 5));
+    // Mirroring happens before rotation.
+    assert_eq!(draw(Rotation::Deg90, (true, false), 1, 2), (13, 6));
+}
+
+#[test]
+fn dimensions_are_logical() {
+    let mut buffer = MemoryDisplayBuffer::new(WIDTH, HEIGHT);
+    buffer.set_rotation(Rotation::Deg270);
+    assert_eq!((buffer.width(), buffer.height()), (HEIGHT, WIDTH));
+    buffer.set_pixel(HEIGHT - 1, WIDTH - 1, Pixel::Black);
+    assert_eq!(black_pixel(&buffer), (15, 0));
+}
+
+/// A 13x3 test pattern with a transparent hole in the middle row.
+fn image() -> Image {
+    let mut image = Image::new(13, 3);
+    for y in 0..3 {
+        for x in 0..13 {
+            if (x * 7 + y * 3) % 5 < 2 {
+                image.set_pixel(x, y, Pixel::Black);
+            }
+        }
+    }
+    image.with_mask(vec![0xff, 0xf8, 0xf0, 0x78, 0xff, 0xf8])
+}
+
+/// Reference blit through get_pixel/set_pixel.
+fn blit_slow(buffer: &mut MemoryDisplayBuffer, x: isize, y: isize, image: &Image, stamp: Option<Pixel>) {
+    for iy in 0..image.height() {
+        for ix in 0..image.width() {
+            let (bx, by) = (x + ix as isize, y + iy as isize);
+            if bx < 0 || by < 0 || bx as usize >= buffer.width() || by as usize >= buffer.height() {
+                continue;
+            }
+            let pixel = image.get_pixel(ix, iy);
+            let value = match stamp {
+                Some(value) if pixel == Pixel::Black => value,
+                Some(_) => continue,
+                None => {
+                    let mask = image.mask().unwrap()[iy * 2 + ix / 8];
+                    if mask & (0x80 >> (ix % 8)) == 0 {
+                        continue;
+                    }
+                    pixel
+                }
+            };
+            buffer.set_pixel(bx as usize, by as usize, value);
+        }
+    }
+}
+
+fn checkerboard(rotation: Rotation) -> MemoryDisplayBuffer {
+    let mut buffer = MemoryDisplayBuffer::new(WIDTH * 2, HEIGHT);
+    buffer.set_rotation(rotation);
+    for y in 0..buffer.height() {
+        for x in 0..buffer.width() {
+            if (x + y) % 2 == 0 {
+                buffer.set_pixel(x, y, Pixel::Black);
+            }
+        }
+    }
+    buffer
+}
+
+#[test]
+fn blits_match_pixel_by_pixel_copies() {
+    let image = image();
+    for rotation in [Rotation::Deg0, Rotation::Deg90] {
+        for &(x, y) in &[(0, 0), (8, 2), (3, 1), (-5, -1), (27, 6), (-20, 0), (40, 40)] {
+            for stamp in [None, Some(Pixel::Black), Some(Pixel::White)] {
+                let mut fast = checkerboard(rotation);
+                let mut slow = checkerboard(rotation);
+                match stamp {
+                    Some(value) => fast.stamp(x, y, &image, value),
+                    None => fast.blit(x, y, &image),
+                }
+                blit_slow(&mut slow, x, y, &image, stamp);
+                assert_eq!(fast, slow, "{:?} at ({}, {}) {:?}", rotation, x, y, stamp);
+            }
+        }
+    }
+}
+
+#[test]
+fn rect_operations_are_clipped() {
+    for rotation in [Rotation::Deg0, Rotation::Deg270] {
+        let mut fast = checkerboard(rotation);
+        let mut slow = checkerboard(rotation);
+        let (width, height) = (fast.width(), fast.height());
+        fast.fill_rect(3, 1, 100, 3, Pixel::Black);
+        fast.hspan(9, 5, 11, Pixel::White);
+        fast.vspan(2, 0, 100, Pixel::White);
+        fast.invert_rect(5, 2, 10, 100);
+        fast.fill_rect(width, 0, 5, 5, Pixel::Black);
+        for y in 0..height {
+            for x in 0..width {
+                if (3..).contains(&x) && (1..4).contains(&y) {
+                    slow.set_pixel(x, y, Pixel::Black);
+                }
+                if (9..20).contains(&x) && y == 5 {
+                    slow.set_pixel(x, y, Pixel::White);
+                }
+                if x == 2 {
+                    slow.set_pixel(x, y, Pixel::White);
+                }
+                if (5..15).contains(&x) && y >= 2 {
+                    let inverted = match slow.get_pixel(x, y) {
+                        Pixel::Black => Pixel::White,
+                        Pixel::White => Pixel::Black,
+                    };
+                    slow.set_pixel(x, y, inverted);
+                }
+            }
+        }
+        assert_eq!(fast, slow, "{:?}", rotation);
+    }
+}
+
+#[test]
+fn fill_marks_every_row_dirty() {
+    let mut buffer = MemoryDisplayBuffer::new(WIDTH, HEIGHT);
+    buffer.mark_clean();
+    buffer.fill(Pixel::Black);
+    assert_eq!(buffer.dirty_rows().count(), HEIGHT);
+    assert!(buffer.as_ref().iter().all(|b| *b == 0));
+}
+
+#[test]
+fn checked_pixel_access() {
+    let mut buffer = MemoryDisplayBuffer::new(WIDTH, HEIGHT);
+    buffer.set_rotation(Rotation::Deg90);
+    assert!(buffer.try_set_pixel(HEIGHT - 1, WIDTH - 1, Pixel::Black).is_ok());
+    assert_eq!(buffer.try_get_pixel(HEIGHT - 1, WIDTH - 1).unwrap(), Pixel::Black);
+    assert!(matches!(
+        buffer.try_set_pixel(HEIGHT, 0, Pixel::Black),
+        Err(Error::OutOfBounds { x: HEIGHT, y: 0, width: HEIGHT, height: WIDTH })
+    ));
+    assert!(buffer.try_get_pixel(0, WIDTH).is_err());
+    assert!(!buffer.contains(0, WIDTH));
+}
+
+#[test]
+fn clip_rect() {
+    let buffer = MemoryDisplayBuffer::new(WIDTH, HEIGHT);
+    assert_eq!(buffer.clip_rect(-3, 2, 5, 100), Some((0, 2, 2, HEIGHT - 2)));
+    assert_eq!(buffer.clip_rect(4, -1, 20, 1), None);
+    assert_eq!(buffer.clip_rect(WIDTH as isize, 0, 1, 1), None);
+    assert_eq!(buffer.clip_rect(0, 0, usize::MAX, 1), Some((0, 0, WIDTH, 1)));
+}
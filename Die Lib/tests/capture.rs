@@ -0,0 +1,78 @@
+//! Saving and loading MemoryDisplayBuffer screenshots.
+#![cfg(feature = "std")]
+
+use rpi_memory_display::{MemoryDisplayBuffer, Pixel, Rotation};
+use std::io::ErrorKind;
+
+fn pattern() -> MemoryDisplayBuffer {
+    let mut buffer = MemoryDisplayBuffer::new(16, 3);
+    buffer.set_pixel(0, 0, Pixel::Black);
+    buffer.set_pixel(9, 1, Pixel::Black);
+    buffer.hspan(4, 2, 12, Pixel::Black);
+    buffer
+}
+
+#[test]
+fn pbm_bytes() {
+    let mut pbm = Vec::new();
+    pattern().write_pbm(&mut pbm).unwrap();
+    assert_eq!(pbm, b"P4\n16 3\n\x80\x00\x00\x40\x0f\xff");
+}
+
+#[test]
+fn pbm_round_trip() {
+    let mut pbm = Vec::new();
+    pattern().write_pbm(&mut pbm).unwrap();
+    assert_eq!(MemoryDisplayBuffer::read_pbm(&pbm[..]).unwrap(), pattern());
+}
+
+#[test]
+fn pbm_header_comments_and_whitespace() {
+    let pbm = b"P4 # screenshot\n8\n  1\n\x81";
+    let buffer = MemoryDisplayBuffer::read_pbm(&pbm[..]).unwrap();
+    assert_eq!(buffer.as_ref(), &[0x7e]);
+}
+
+#[test]
+fn loading_rejects_bad_images() {
+    let err = MemoryDisplayBuffer::read_pbm(&b"P1\n8 1\n0"[..]).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+    let err = MemoryDisplayBuffer::read_pbm(&b"P4\n7 1\n\x00"[..]).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+    let err = MemoryDisplayBuffer::read_pbm(&b"P4\n8 2\n\x00"[..]).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+}
+
+#[test]
+fn loading_rejects_huge_sizes_before_reading_pixels() {
+    for header in ["P4\n4096 8\n", "P4\n8 99999999999\n", "P4\n18446744073709551608 18446744073709551615\n"] {
+        let err = MemoryDisplayBuffer::read_pbm(header.as_bytes()).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData, "{}", header);
+    }
+    let err = MemoryDisplayBuffer::read_pbm(&b"P4\n7 1\n"[..]).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+}
+
+#[test]
+fn screenshots_are_in_logical_orientation() {
+    let mut buffer = MemoryDisplayBuffer::new(16, 8);
+    buffer.set_rotation(Rotation::Deg90);
+    buffer.set_pixel(0, 0, Pixel::Black);
+    let image = buffer.to_image();
+    assert_eq!((image.width(), image.height()), (8, 16));
+    assert_eq!(image.get_pixel(0, 0), Pixel::Black);
+
+    let mut pbm = Vec::new();
+    buffer.write_pbm(&mut pbm).unwrap();
+    let loaded = MemoryDisplayBuffer::read_pbm(&pbm[..]).unwrap();
+    assert_eq!(loaded.to_image(), image);
+}
+
+#[cfg(feature = "png")]
+#[test]
+fn png_round_trip() {
+    let mut png = Vec::new();
+    pattern().write_png(&mut png).unwrap();
+    assert_eq!(&png[1..4], b"PNG");
+    assert_eq!(MemoryDisplayBuffer::read_png(&png[..]).unwrap(), pattern());
+}
@@ -0,0 +1,446 @@
+//! MemoryDisplay against an in-memory recording transport.
+
+use core::convert::Infallible;
+use embedded_hal::digital::{self, OutputPin};
+use embedded_hal::spi::{self, SpiBus};
+use rpi_memory_display::{Error, MemoryDisplay, MemoryDisplayBuffer, Pixel, UpdateStats};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+const WIDTH: usize = 16;
+const HEIGHT: usize = 4;
+
+/// Records every byte written while chip select is high.
+#[derive(Debug, Default)]
+struct Recorder {
+    written: Vec<u8>,
+}
+
+impl spi::ErrorType for Recorder {
+    type Error = Infallible;
+}
+
+impl SpiBus<u8> for Recorder {
+    fn read(&mut self, _words: &mut [u8]) -> Result<(), Infallible> {
+        Ok(())
+    }
+
+    fn write(&mut self, words: &[u8]) -> Result<(), Infallible> {
+        self.written.extend_from_slice(words);
+        Ok(())
+    }
+
+    fn transfer(&mut self, _read: &mut [u8], write: &[u8]) -> Result<(), Infallible> {
+        self.write(write)
+    }
+
+    fn transfer_in_place(&mut self, _words: &mut [u8]) -> Result<(), Infallible> {
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), Infallible> {
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default)]
+struct Pin;
+
+impl digital::ErrorType for Pin {
+    type Error = Infallible;
+}
+
+impl OutputPin for Pin {
+    fn set_low(&mut self) -> Result<(), Infallible> {
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Infallible> {
+        Ok(())
+    }
+}
+
+fn display() -> MemoryDisplay<Recorder, Pin> {
+    let mut display = MemoryDisplay::with_spi(Recorder::default(), Pin, WIDTH, HEIGHT).unwrap();
+    display.clear().unwrap();
+    display
+}
+
+#[test]
+fn update_sends_changed_lines_and_reports_stats() {
+    let mut display = display();
+    let mut buffer = MemoryDisplayBuffer::new(WIDTH, HEIGHT);
+    buffer.set_pixel(0, 2, Pixel::Black);
+    let stats = display.update(&buffer).unwrap();
+    assert_eq!(stats, UpdateStats { lines: 1, bytes: 6 });
+    assert_eq!(display.changed_lines(), &[2]);
+
+    let stats = display.update(&buffer).unwrap();
+    assert_eq!(stats, UpdateStats { lines: 0, bytes: 2 });
+}
+
+#[test]
+fn update_rows_sends_only_named_lines() {
+    let mut display = display();
+    let buffer = MemoryDisplayBuffer::new(WIDTH, HEIGHT);
+    let stats = display.update_rows(&buffer, 1..3).unwrap();
+    assert_eq!(stats.lines, 2);
+    assert_eq!(display.changed_lines(), &[1, 2]);
+
+    let stats = display.update_rows(&buffer, 3..10).unwrap();
+    assert_eq!(display.changed_lines(), &[3]);
+    assert_eq!(stats.lines, 1);
+}
+
+#[test]
+fn refresh_sends_every_line() {
+    let mut display = display();
+    let buffer = MemoryDisplayBuffer::new(WIDTH, HEIGHT);
+    display.update(&buffer).unwrap();
+    let stats = display.refresh(&buffer).unwrap();
+    assert_eq!(stats, UpdateStats { lines: 4, bytes: 2 + 4 * 4 });
+}
+
+#[test]
+fn update_buffer_only_compares_dirty_rows() {
+    let mut display = display();
+    let mut buffer = MemoryDisplayBuffer::new(WIDTH, HEIGHT);
+    assert_eq!(display.update_buffer(&mut buffer).unwrap().lines, 0);
+    assert_eq!(buffer.dirty_rows().count(), 0);
+
+    buffer.set_pixel(3, 1, Pixel::Black);
+    buffer.set_pixel(3, 3, Pixel::White);
+    assert_eq!(buffer.dirty_rows().collect::<Vec<_>>(), vec![1, 3]);
+    let stats = display.update_buffer(&mut buffer).unwrap();
+    assert_eq!(stats.lines, 1);
+    assert_eq!(display.changed_lines(), &[1]);
+    assert!(!buffer.is_row_dirty(1));
+}
+
+#[test]
+fn wrong_sized_frames_are_rejected() {
+    let mut display = display();
+    let sent = display.commands_sent();
+    for len in [0, 2 * HEIGHT - 1, 2 * HEIGHT + 1] {
+        let err = display.update(vec![0; len]).unwrap_err();
+        assert!(matches!(err, Error::BufferSize { expected: 8, actual } if actual == len));
+    }
+    let mut buffer = MemoryDisplayBuffer::new(WIDTH, HEIGHT + 1);
+    assert!(display.update_buffer(&mut buffer).is_err());
+    assert!(display.refresh(vec![0; 4]).is_err());
+    assert_eq!(display.commands_sent(), sent);
+}
+
+#[test]
+fn commands_reach_the_bus() {
+    let mut display = display();
+    let mut buffer = MemoryDisplayBuffer::new(WIDTH, HEIGHT);
+    buffer.fill(Pixel::Black);
+    display.update_rows(&buffer, 0..1).unwrap();
+    let (spi, _) = display.release();
+    assert_eq!(spi.written, vec![0x60, 0x00, 0x80, 0x80, 0x00, 0x00, 0x00, 0x00]);
+}
+
+/// Fails every write with the error made by its function.
+struct Failing<E>(fn() -> E);
+
+impl<E: spi::Error> spi::ErrorType for Failing<E> {
+    type Error = E;
+}
+
+impl<E: spi::Error> SpiBus<u8> for Failing<E> {
+    fn read(&mut self, _words: &mut [u8]) -> Result<(), E> {
+        Err((self.0)())
+    }
+
+    fn write(&mut self, _words: &[u8]) -> Result<(), E> {
+        Err((self.0)())
+    }
+
+    fn transfer(&mut self, _read: &mut [u8], _write: &[u8]) -> Result<(), E> {
+        Err((self.0)())
+    }
+
+    fn transfer_in_place(&mut self, _words: &mut [u8]) -> Result<(), E> {
+        Err((self.0)())
+    }
+
+    fn flush(&mut self) -> Result<(), E> {
+        Ok(())
+    }
+}
+
+#[test]
+fn bus_errors_keep_their_details() {
+    // Buses reporting an Error, like RppalSpi, pass it on unchanged
+    let bus = Failing(|| Error::BufferSize { expected: 1, actual: 2 });
+    let mut display = MemoryDisplay::with_spi(bus, Pin, WIDTH, HEIGHT).unwrap();
+    let err = display.clear().unwrap_err();
+    assert!(matches!(err, Error::BufferSize { expected: 1, actual: 2 }));
+
+    // Other buses' errors keep their kind
+    let mut display = MemoryDisplay::with_spi(Failing(|| spi::ErrorKind::Overrun), Pin, WIDTH, HEIGHT).unwrap();
+    let err = display.clear().unwrap_err();
+    assert!(matches!(err, Error::Transport(spi::ErrorKind::Overrun)));
+}
+
+/// Records like Recorder, but fails the next write once `fail` is set.
+struct Flaky {
+    fail: Arc<AtomicBool>,
+    written: Vec<u8>,
+}
+
+impl spi::ErrorType for Flaky {
+    type Error = spi::ErrorKind;
+}
+
+impl SpiBus<u8> for Flaky {
+    fn read(&mut self, _words: &mut [u8]) -> Result<(), spi::ErrorKind> {
+        Ok(())
+    }
+
+    fn write(&mut self, words: &[u8]) -> Result<(), spi::ErrorKind> {
+        if self.fail.swap(false, Ordering::SeqCst) {
+            return Err(spi::ErrorKind::Other);
+        }
+        self.written.extend_from_slice(words);
+        Ok(())
+    }
+
+    fn transfer(&mut self, _read: &mut [u8], write: &[u8]) -> Result<(), spi::ErrorKind> {
+        self.write(write)
+    }
+
+    fn transfer_in_place(&mut self, _words: &mut [u8]) -> Result<(), spi::ErrorKind> {
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), spi::ErrorKind> {
+        Ok(())
+    }
+}
+
+#[test]
+fn failed_commands_leave_the_next_one_clean() {
+    let fail = Arc::new(AtomicBool::new(false));
+    let bus = Flaky { fail: fail.clone(), written: vec![] };
+    let mut flaky = MemoryDisplay::with_spi(bus, Pin, WIDTH, HEIGHT).unwrap();
+    flaky.clear().unwrap();
+    let mut buffer = MemoryDisplayBuffer::new(WIDTH, HEIGHT);
+    buffer.set_pixel(0, 2, Pixel::Black);
+    fail.store(true, Ordering::SeqCst);
+    assert!(flaky.update(&buffer).is_err());
+
+    // The rows that failed aren't taken as on the panel, so every row
+    // is compared and sent again, without the failed command's bytes.
+    let stats = flaky.update(&buffer).unwrap();
+    assert_eq!(stats, UpdateStats { lines: 4, bytes: 2 + 4 * 4 });
+    let (spi, _) = flaky.release();
+
+    // The failed update still flipped VCOM, so it stands in for a
+    // toggle whose bytes never reached the bus.
+    let mut expected = display();
+    expected.toggle_vcom().unwrap();
+    expected.refresh(&buffer).unwrap();
+    let (mut expected, _) = expected.release();
+    expected.written.drain(2..4);
+    assert_eq!(spi.written, expected.written);
+}
+
+/// Remembers the last level it was set to.
+#[derive(Debug, Default, Clone)]
+struct Level(Arc<AtomicUsize>);
+
+impl Level {
+    fn get(&self) -> usize {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+impl digital::ErrorType for Level {
+    type Error = Infallible;
+}
+
+impl OutputPin for Level {
+    fn set_low(&mut self) -> Result<(), Infallible> {
+        self.0.store(0, Ordering::SeqCst);
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Infallible> {
+        self.0.store(1, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+#[test]
+fn sleep_clears_and_turns_the_display_off() {
+    let disp = Level::default();
+    let mut display = display().with_disp(disp.clone()).unwrap();
+    assert_eq!(disp.get(), 1);
+    assert!(!display.is_asleep());
+
+    let sent = display.commands_sent();
+    display.sleep().unwrap();
+    assert_eq!(disp.get(), 0);
+    assert!(display.is_asleep());
+    assert_eq!(display.commands_sent(), sent + 1);
+
+    display.wake().unwrap();
+    assert_eq!(disp.get(), 1);
+    assert!(!display.is_asleep());
+}
+
+/// Background VCOM maintenance needs threads.
+#[cfg(feature = "std")]
+mod vcom {
+    use super::*;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn software_vcom_maintenance_toggles_while_idle() {
+        let display = display().maintain_vcom(Duration::from_millis(5));
+        thread::sleep(Duration::from_millis(100));
+        let display = display.into_inner();
+        assert!(display.commands_sent() > 2);
+        let (spi, _) = display.release();
+        // After the clear, only VCOM-only commands with alternating VCOM.
+        assert_eq!(&spi.written[2..6], &[0x00, 0x00, 0x40, 0x00]);
+    }
+
+    /// Counts rising edges.
+    #[derive(Debug, Default)]
+    struct EdgeCounter {
+        high: bool,
+        rising: Arc<AtomicUsize>,
+    }
+
+    impl digital::ErrorType for EdgeCounter {
+        type Error = Infallible;
+    }
+
+    impl OutputPin for EdgeCounter {
+        fn set_low(&mut self) -> Result<(), Infallible> {
+            self.high = false;
+            Ok(())
+        }
+
+        fn set_high(&mut self) -> Result<(), Infallible> {
+            if !self.high {
+                self.rising.fetch_add(1, Ordering::SeqCst);
+            }
+            self.high = true;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn extcomin_maintenance_stops_when_dropped() {
+        let pin = EdgeCounter::default();
+        let rising = pin.rising.clone();
+        let display = display().maintain_vcom_extcomin(pin, Duration::from_millis(10));
+        thread::sleep(Duration::from_millis(100));
+        drop(display);
+        let edges = rising.load(Ordering::SeqCst);
+        assert!(edges > 2);
+        thread::sleep(Duration::from_millis(50));
+        assert_eq!(rising.load(Ordering::SeqCst), edges);
+    }
+
+    #[test]
+    fn software_vcom_maintenance_pauses_while_asleep() {
+        let mut display = display().with_disp(Level::default()).unwrap();
+        display.sleep().unwrap();
+        let sent = display.commands_sent();
+        let display = display.maintain_vcom(Duration::from_millis(5));
+        thread::sleep(Duration::from_millis(50));
+        assert_eq!(display.lock().commands_sent(), sent);
+
+        display.lock().wake().unwrap();
+        thread::sleep(Duration::from_millis(50));
+        assert!(display.lock().commands_sent() > sent);
+    }
+
+    #[test]
+    fn extcomin_maintenance_pauses_while_asleep() {
+        let pin = EdgeCounter::default();
+        let rising = pin.rising.clone();
+        let mut display = display().with_disp(Level::default()).unwrap();
+        display.sleep().unwrap();
+        let display = display.maintain_vcom_extcomin(pin, Duration::from_millis(10));
+        thread::sleep(Duration::from_millis(50));
+        assert_eq!(rising.load(Ordering::SeqCst), 0);
+
+        display.lock().wake().unwrap();
+        thread::sleep(Duration::from_millis(50));
+        assert!(rising.load(Ordering::SeqCst) > 0);
+    }
+
+    #[test]
+    fn presented_frames_are_sent_in_the_background() {
+        let display = display().maintain_vcom(Duration::from_secs(60)).flush_in_background();
+        let mut frame = MemoryDisplayBuffer::new(WIDTH, HEIGHT);
+        frame.set_pixel(1, 1, Pixel::Black);
+        display.present(&mut frame).unwrap();
+        assert_eq!(frame.dirty_rows().count(), 0);
+        display.wait().unwrap();
+        assert_eq!(display.lock().changed_lines(), &[1]);
+    }
+
+    #[test]
+    fn intermediate_frames_are_dropped() {
+        let display = display().maintain_vcom(Duration::from_secs(60)).flush_in_background();
+        let sent = display.lock().commands_sent();
+        let mut frame = MemoryDisplayBuffer::new(WIDTH, HEIGHT);
+        {
+            // Holding the display keeps the flush thread from sending,
+            // but presenting still returns straight away.
+            let _held = display.lock();
+            for y in 0..HEIGHT {
+                frame.set_pixel(y, y, Pixel::Black);
+                display.present(&mut frame).unwrap();
+            }
+        }
+        display.wait().unwrap();
+        let mut display = display.into_inner().into_inner();
+        assert!(display.commands_sent() - sent <= 2);
+        // Rows from the skipped frames were sent too.
+        assert_eq!(display.update(&frame).unwrap().lines, 0);
+    }
+
+    #[test]
+    fn dropping_sends_the_last_frame() {
+        let display = display().maintain_vcom(Duration::from_secs(60)).flush_in_background();
+        let sent = display.lock().commands_sent();
+        let mut frame = MemoryDisplayBuffer::new(WIDTH, HEIGHT);
+        frame.fill(Pixel::Black);
+        display.present(&mut frame).unwrap();
+        let mut display = display.into_inner().into_inner();
+        assert!(display.commands_sent() > sent);
+        assert_eq!(display.update(&frame).unwrap().lines, 0);
+    }
+
+    #[test]
+    fn flush_errors_are_returned_by_wait() {
+        let display = MemoryDisplay::with_spi(Failing(|| spi::ErrorKind::Overrun), Pin, WIDTH, HEIGHT).unwrap();
+        let display = display.maintain_vcom(Duration::from_secs(60)).flush_in_background();
+        let mut frame = MemoryDisplayBuffer::new(WIDTH, HEIGHT);
+        frame.fill(Pixel::Black);
+        display.present(&mut frame).unwrap();
+        let err = display.wait().unwrap_err();
+        assert!(matches!(err, Error::Transport(spi::ErrorKind::Overrun)));
+    }
+
+    #[test]
+    fn wait_returns_when_the_flush_thread_panics() {
+        let bus = Failing::<spi::ErrorKind>(|| panic!("bus unplugged"));
+        let display = MemoryDisplay::with_spi(bus, Pin, WIDTH, HEIGHT).unwrap();
+        let display = display.maintain_vcom(Duration::from_secs(60)).flush_in_background();
+        let mut frame = MemoryDisplayBuffer::new(WIDTH, HEIGHT);
+        frame.fill(Pixel::Black);
+        let _ = display.present(&mut frame);
+        assert!(matches!(display.wait(), Err(Error::FlushStopped)));
+        assert!(matches!(display.present(&mut frame), Err(Error::FlushStopped)));
+    }
+}
@@ -0,0 +1,77 @@
+//! Greyscale to 1-bit conversion.
+
+use rpi_memory_display::dither::{luma, Dither};
+use rpi_memory_display::Pixel;
+
+const MODES: [Dither; 5] = [
+    Dither::Threshold(127),
+    Dither::FloydSteinberg,
+    Dither::Atkinson,
+    Dither::Bayer4,
+    Dither::Bayer8,
+];
+
+fn black_count(pixels: &[Pixel]) -> usize {
+    pixels.iter().filter(|p| **p == Pixel::Black).count()
+}
+
+#[test]
+fn black_and_white_are_kept() {
+    for mode in MODES {
+        assert_eq!(black_count(&mode.apply(&[0; 64], 8, 8)), 64, "{:?}", mode);
+        assert_eq!(black_count(&mode.apply(&[255; 64], 8, 8)), 0, "{:?}", mode);
+    }
+}
+
+#[test]
+fn threshold() {
+    let pixels = Dither::Threshold(100).apply(&[99, 100, 101], 3, 1);
+    assert_eq!(pixels, vec![Pixel::Black, Pixel::Black, Pixel::White]);
+    assert_eq!(Dither::default(), Dither::Threshold(127));
+}
+
+#[test]
+fn mid_grey_is_about_half_black() {
+    for mode in &MODES[1..] {
+        let black = black_count(&mode.apply(&[128; 256], 16, 16));
+        assert!((112..=144).contains(&black), "{:?}: {}", mode, black);
+    }
+    assert_eq!(black_count(&Dither::Threshold(127).apply(&[128; 256], 16, 16)), 0);
+}
+
+#[test]
+fn bayer_pattern() {
+    // A quarter grey lights up the lowest quarter of the matrix.
+    let pixels = Dither::Bayer4.apply(&[64; 16], 4, 4);
+    let white: Vec<usize> = (0..16).filter(|i| pixels[*i] == Pixel::White).collect();
+    assert_eq!(white, vec![0, 2, 8, 10]);
+}
+
+#[test]
+fn patterns_follow_the_origin() {
+    // An image drawn at (1, 2) continues the pattern of one at (0, 0)
+    let whole = Dither::Bayer8.apply(&[100; 64], 8, 8);
+    let part = Dither::Bayer8.apply_at(&[100; 15], 5, 3, (1, 2));
+    for (i, pixel) in part.iter().enumerate() {
+        assert_eq!(*pixel, whole[(2 + i / 5) * 8 + 1 + i % 5]);
+    }
+    let image = Dither::Bayer8.apply_to_image_at(&[100; 15], 5, 3, (1, 2));
+    assert_eq!(image.get_pixel(4, 2), part[14]);
+}
+
+#[test]
+fn images_match_pixels() {
+    let gray: Vec<u8> = (0..30).map(|i| (i * 8) as u8).collect();
+    let pixels = Dither::FloydSteinberg.apply(&gray, 10, 3);
+    let image = Dither::FloydSteinberg.apply_to_image(&gray, 10, 3);
+    for (i, pixel) in pixels.iter().enumerate() {
+        assert_eq!(image.get_pixel(i % 10, i / 10), *pixel);
+    }
+}
+
+#[test]
+fn luma_weights() {
+    assert_eq!(luma(255, 255, 255), 255);
+    assert_eq!(luma(0, 0, 0), 0);
+    assert_eq!(luma(0, 255, 0), 150);
+}
@@ -0,0 +1,132 @@
+//! Golden byte streams for the Memory Display command encoder.
+
+use rpi_memory_display::encoder::{line_header_10, Command, Encoder};
+use rpi_memory_display::panel::{AddressWidth, Panel};
+use rpi_memory_display::Error;
+
+// A 16x3 display: two bytes per row.
+const WIDTH: usize = 16;
+const HEIGHT: usize = 3;
+
+const WHITE: [u8; 6] = [0xff; 6];
+
+#[test]
+fn clear() {
+    let mut encoder = Encoder::new(WIDTH, HEIGHT).unwrap();
+    assert_eq!(
+        encoder.clear(),
+        Command { bytes: vec![0x60, 0x00], lines: vec![] }
+    );
+}
+
+#[test]
+fn full_write() {
+    let mut encoder = Encoder::new(WIDTH, HEIGHT).unwrap();
+    let next = [0x01, 0x02, 0x03, 0x04, 0x05, 0x06];
+    let cmd = encoder.update(None, &next);
+    assert_eq!(
+        cmd.bytes,
+        vec![
+            0xc0,
+            0x80, 0x01, 0x02, 0x00,
+            0x40, 0x03, 0x04, 0x00,
+            0xc0, 0x05, 0x06, 0x00,
+            0x00,
+        ]
+    );
+    assert_eq!(cmd.lines, vec![0, 1, 2]);
+}
+
+#[test]
+fn full_write_when_previous_frame_size_differs() {
+    let mut encoder = Encoder::new(WIDTH, HEIGHT).unwrap();
+    let next = [0xff; 6];
+    let cmd = encoder.update(Some(&[0xff; 4]), &next);
+    assert_eq!(cmd.lines, vec![0, 1, 2]);
+}
+
+#[test]
+fn partial_diff() {
+    let mut encoder = Encoder::new(WIDTH, HEIGHT).unwrap();
+    let next = [0xff, 0xff, 0xff, 0x00, 0xff, 0xff];
+    let cmd = encoder.update(Some(&WHITE), &next);
+    assert_eq!(
+        cmd.bytes,
+        vec![
+            0xc0,
+            0x40, 0xff, 0x00, 0x00,
+            0x00,
+        ]
+    );
+    assert_eq!(cmd.lines, vec![1]);
+}
+
+#[test]
+fn unchanged_frame_sends_no_lines() {
+    let mut encoder = Encoder::new(WIDTH, HEIGHT).unwrap();
+    let cmd = encoder.update(Some(&WHITE), &WHITE);
+    assert_eq!(cmd.bytes, vec![0xc0, 0x00]);
+    assert!(cmd.lines.is_empty());
+}
+
+#[test]
+fn vcom_alternates_between_commands() {
+    let mut encoder = Encoder::new(WIDTH, HEIGHT).unwrap();
+    assert!(encoder.vcom());
+    assert_eq!(encoder.clear().bytes, vec![0x60, 0x00]);
+    assert!(!encoder.vcom());
+    assert_eq!(encoder.update(Some(&WHITE), &WHITE).bytes, vec![0x80, 0x00]);
+    assert!(encoder.vcom());
+    assert_eq!(encoder.update(Some(&WHITE), &WHITE).bytes, vec![0xc0, 0x00]);
+    assert_eq!(encoder.clear().bytes, vec![0x20, 0x00]);
+    assert_eq!(encoder.toggle_vcom().bytes, vec![0x40, 0x00]);
+    assert_eq!(encoder.toggle_vcom().bytes, vec![0x00, 0x00]);
+}
+
+#[test]
+fn ten_bit_addresses() {
+    let panel = Panel {
+        width: WIDTH,
+        height: HEIGHT,
+        address_width: AddressWidth::Bits10,
+        max_clock_hz: 0,
+    };
+    let mut encoder = Encoder::for_panel(&panel).unwrap();
+    let next = [0x01, 0x02, 0x03, 0x04, 0x05, 0x06];
+    let cmd = encoder.update(None, &next);
+    assert_eq!(
+        cmd.bytes,
+        vec![
+            0xc2, 0x00, 0x01, 0x02,
+            0x01, 0x00, 0x03, 0x04,
+            0x03, 0x00, 0x05, 0x06,
+            0x00, 0x00,
+        ]
+    );
+    assert_eq!(cmd.lines, vec![0, 1, 2]);
+    assert_eq!(encoder.update(Some(&next), &next).bytes, vec![0x80, 0x00, 0x00, 0x00]);
+}
+
+#[test]
+fn ten_bit_header_for_last_ls032_line() {
+    // Line 536 is 0b10_0001_1000, sent LSB first.
+    assert_eq!(line_header_10(0, 535), [0x00, 0x61]);
+}
+
+#[test]
+fn tall_panels_use_ten_bit_addresses() {
+    assert_eq!(Encoder::new(400, 240).unwrap().address_width(), AddressWidth::Bits8);
+    assert_eq!(Encoder::for_panel(&Panel::LS032B7DD02).unwrap().address_width(), AddressWidth::Bits10);
+    assert_eq!(Panel::LS032B7DD02.buffer().height(), 536);
+}
+
+#[test]
+fn panels_too_tall_for_their_addresses_are_rejected() {
+    let panel = Panel { address_width: AddressWidth::Bits8, ..Panel::LS032B7DD02 };
+    match Encoder::for_panel(&panel) {
+        Err(Error::OutOfBounds { y: 535, height: 255, .. }) => {}
+        other => panic!("expected OutOfBounds, got {:?}", other.map(|_| ())),
+    }
+    assert!(Encoder::new(400, 1024).is_err());
+    assert!(Encoder::new(400, 1023).is_ok());
+}
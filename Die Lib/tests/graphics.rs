@@ -0,0 +1,93 @@
+//! DrawTarget fast paths must match drawing pixel by pixel.
+#![cfg(feature = "embedded-graphics-core")]
+
+use embedded_graphics_core::geometry::{Point, Size};
+use embedded_graphics_core::pixelcolor::BinaryColor;
+use embedded_graphics_core::primitives::{PointsIter, Rectangle};
+use embedded_graphics_core::draw_target::DrawTarget;
+use rpi_memory_display::{MemoryDisplayBuffer, Pixel, Rotation};
+
+const WIDTH: usize = 32;
+const HEIGHT: usize = 4;
+
+fn areas() -> Vec<Rectangle> {
+    vec![
+        Rectangle::new(Point::new(3, 1), Size::new(2, 2)),
+        Rectangle::new(Point::new(5, 0), Size::new(20, 4)),
+        Rectangle::new(Point::new(8, 1), Size::new(16, 1)),
+        Rectangle::new(Point::new(-3, -1), Size::new(10, 3)),
+        Rectangle::new(Point::new(28, 2), Size::new(10, 10)),
+        Rectangle::new(Point::new(40, 0), Size::new(4, 4)),
+    ]
+}
+
+fn pattern(i: usize) -> BinaryColor {
+    match i % 3 {
+        0 => BinaryColor::On,
+        _ => BinaryColor::Off,
+    }
+}
+
+#[test]
+fn fill_solid_matches_draw_iter() {
+    for area in areas() {
+        let mut fast = MemoryDisplayBuffer::new(WIDTH, HEIGHT);
+        let mut slow = MemoryDisplayBuffer::new(WIDTH, HEIGHT);
+        fast.fill_solid(&area, BinaryColor::On).unwrap();
+        slow.draw_iter(area.points().map(|p| embedded_graphics_core::Pixel(p, BinaryColor::On)))
+            .unwrap();
+        assert_eq!(fast, slow, "{:?}", area);
+    }
+}
+
+#[test]
+fn fill_contiguous_matches_draw_iter() {
+    for area in areas() {
+        let mut fast = MemoryDisplayBuffer::new(WIDTH, HEIGHT);
+        let mut slow = MemoryDisplayBuffer::new(WIDTH, HEIGHT);
+        fast.fill(Pixel::Black);
+        slow.fill(Pixel::Black);
+        fast.fill_contiguous(&area, (0..).map(pattern)).unwrap();
+        slow.draw_iter(
+            area.points()
+                .enumerate()
+                .map(|(i, p)| embedded_graphics_core::Pixel(p, pattern(i))),
+        )
+        .unwrap();
+        assert_eq!(fast, slow, "{:?}", area);
+    }
+}
+
+#[test]
+fn fills_match_draw_iter_when_rotated() {
+    for rotation in [Rotation::Deg90, Rotation::Deg180, Rotation::Deg270] {
+        for area in areas() {
+            let mut fast = MemoryDisplayBuffer::new(WIDTH, WIDTH);
+            let mut slow = MemoryDisplayBuffer::new(WIDTH, WIDTH);
+            fast.set_rotation(rotation);
+            slow.set_rotation(rotation);
+            fast.set_mirror(true, false);
+            slow.set_mirror(true, false);
+            fast.fill_solid(&area, BinaryColor::On).unwrap();
+            fast.fill_contiguous(&area, (0..).map(pattern)).unwrap();
+            slow.draw_iter(area.points().map(|p| embedded_graphics_core::Pixel(p, BinaryColor::On)))
+                .unwrap();
+            slow.draw_iter(
+                area.points()
+                    .enumerate()
+                    .map(|(i, p)| embedded_graphics_core::Pixel(p, pattern(i))),
+            )
+            .unwrap();
+            assert_eq!(fast, slow, "{:?} {:?}", rotation, area);
+        }
+    }
+}
+
+#[test]
+fn on_is_black() {
+    let mut buffer = MemoryDisplayBuffer::new(WIDTH, HEIGHT);
+    buffer.draw_iter(Some(embedded_graphics_core::Pixel(Point::new(1, 2), BinaryColor::On)))
+        .unwrap();
+    assert_eq!(buffer.get_pixel(1, 2), Pixel::Black);
+    assert_eq!(buffer.get_pixel(0, 2), Pixel::White);
+}
@@ -4,8 +4,9 @@ import time
 import json
 from simplenote import Simplenote
 
-DIR = "/home/kramwriter/folder/simplenote"
-CREDS_FILE = "/home/kramwriter/.simplenote_creds"
+# KramWriterOS passes its configured paths; the defaults are the old fixed ones
+DIR = os.environ.get("KRAMWRITER_SIMPLENOTE_DIR", "/home/kramwriter/folder/simplenote")
+CREDS_FILE = os.environ.get("KRAMWRITER_SIMPLENOTE_CREDS", "/home/kramwriter/.simplenote_creds")
 INDEX_FILE = os.path.join(DIR, ".sync_index.json")
 
 def get_title(content):
@@ -0,0 +1,229 @@
+use crate::display::SharpDisplay;
+use crate::context::{Context, KeyboardLayout};
+use crate::device::device;
+use crate::keymap::Keymap;
+use crate::paths::paths;
+use crate::settings::SettingsStore;
+use crate::pages::{self, Page, Action};
+use termion::event::Key;
+use anyhow::Result;
+use std::sync::mpsc::{Receiver, RecvTimeoutError};
+use std::time::{Duration, Instant};
+
+/// While asleep nothing is drawn, so wake up less often
+const ASLEEP_POLL: Duration = Duration::from_secs(1);
+
+/// Without a key press, the top page is ticked this often
+const TICK: Duration = Duration::from_millis(100);
+
+/// Where the App gets its key presses from: the keyboard thread in
+/// main, or a script in tests.
+pub trait KeySource {
+    /// Waits up to `timeout` for the next key. A timeout ticks the top
+    /// page; a disconnected source ends `App::run`.
+    fn next_key(&mut self, timeout: Duration) -> Result<Key, RecvTimeoutError>;
+}
+
+impl KeySource for Receiver<Key> {
+    fn next_key(&mut self, timeout: Duration) -> Result<Key, RecvTimeoutError> {
+        self.recv_timeout(timeout)
+    }
+}
+
+/// The page stack and the screen it is drawn on.
+pub struct App {
+    display: SharpDisplay,
+    ctx: Context,
+    /// Saves `ctx` whenever a key or tick changes it
+    settings: Option<SettingsStore>,
+    /// Translates keys for `ctx.layout`
+    keymap: Keymap,
+    stack: Vec<Box<dyn Page>>,
+    /// Blank the display after this long without a key press
+    idle_timeout: Duration,
+}
+
+impl App {
+    /// Starts at the logo page with the settings saved in the data
+    /// directory.
+    pub fn new(display: SharpDisplay) -> Self {
+        Self::with_settings(display, SettingsStore::new(paths().settings()))
+    }
+
+    /// Starts at the logo page with the settings loaded from `settings`,
+    /// which is kept up to date.
+    pub fn with_settings(display: SharpDisplay, mut settings: SettingsStore) -> Self {
+        let ctx = settings.load();
+        let mut app = Self::with_context(display, ctx);
+        app.settings = Some(settings);
+        app
+    }
+
+    /// Starts at the logo page with `ctx`, which isn't saved.
+    pub fn with_context(display: SharpDisplay, ctx: Context) -> Self {
+        let startup_page = Box::new(pages::startup::LogoPage::new());
+        let keymap = keymap_for(&ctx.layout);
+
+        Self {
+            display,
+            ctx,
+            settings: None,
+            keymap,
+            stack: vec![startup_page],
+            idle_timeout: device().idle_timeout,
+        }
+    }
+
+    /// Blanks the display after `timeout` without a key press instead
+    /// of the configured idle timeout.
+    pub fn with_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.idle_timeout = timeout;
+        self
+    }
+
+    pub fn display(&self) -> &SharpDisplay {
+        &self.display
+    }
+
+    pub fn context(&self) -> &Context {
+        &self.ctx
+    }
+
+    /// Runs the page stack until it exits, Ctrl-x is pressed or the
+    /// keys run out.
+    pub fn run(&mut self, keys: &mut impl KeySource) -> Result<()> {
+        // Initial render
+        self.render()?;
+        let mut last_input = Instant::now();
+
+        loop {
+            // 1. WAIT WITH TIMEOUT
+            // Check for a key, but only wait for TICK so tick() can run.
+            let asleep = self.display.is_asleep();
+            let timeout = if asleep { ASLEEP_POLL } else { TICK };
+            let key_event = match keys.next_key(timeout) {
+                Ok(key) => Some(self.keymap.translate(key)),
+                Err(RecvTimeoutError::Timeout) => None,
+                Err(RecvTimeoutError::Disconnected) => return Ok(()),
+            };
+
+            // Idle policy: blank after the idle timeout, wake on any key.
+            // The waking key is swallowed so it can't type into a page
+            // the user can't see yet. Pages don't tick while asleep.
+            if key_event.is_some() {
+                last_input = Instant::now();
+                if asleep {
+                    self.display.wake()?;
+                    self.render()?;
+                    continue;
+                }
+            } else if asleep {
+                continue;
+            } else if last_input.elapsed() >= self.idle_timeout {
+                self.display.sleep()?;
+                continue;
+            }
+
+            // Handle Global Exit
+            if let Some(Key::Ctrl('x')) = key_event {
+                self.display.clear(&self.ctx);
+                self.display.update()?;
+                return Ok(());
+            }
+
+            // Global Screenshot (saved with the app's data)
+            if let Some(Key::F(12)) = key_event {
+                let dir = paths().screenshots();
+                let name = self.ctx.now().format("screenshot-%Y%m%d-%H%M%S.png").to_string();
+                // A failed screenshot shouldn't end the writing session
+                let _ = std::fs::create_dir_all(&dir);
+                let _ = self.display.save_screenshot(&dir.join(name));
+                continue;
+            }
+
+            // 2. PAGE LOGIC
+            let mut should_render = false;
+            let action = if let Some(top_page) = self.stack.last_mut() {
+                match key_event {
+                    Some(key) => {
+                        should_render = true; // Always render if user pressed a key
+                        top_page.update(key, &mut self.ctx)
+                    }
+                    None => {
+                        // This is the "Automatic" part: call tick() even without input.
+                        let tick_action = top_page.tick(&mut self.ctx);
+                        
+                        // If tick returns anything other than None, we should redraw.
+                        if !matches!(tick_action, Action::None) {
+                            should_render = true;
+                        }
+                        tick_action
+                    }
+                }
+            } else {
+                Action::Exit
+            };
+
+            if self.keymap.layout() != &self.ctx.layout {
+                self.keymap = keymap_for(&self.ctx.layout);
+            }
+
+            // A setting that can't be saved still applies to this
+            // session, so don't end it over a full or read-only disk
+            if let Some(settings) = &mut self.settings {
+                let _ = settings.save(&self.ctx);
+            }
+
+            // 3. PROCESS ACTION
+            match action {
+                Action::Push(new_page) => {
+                    self.stack.push(new_page);
+                    should_render = true;
+                },
+                Action::Pop => { 
+                    self.stack.pop(); 
+                    should_render = true;
+                },
+                Action::Replace(new_page) => {
+                    self.stack.pop();
+                    self.stack.push(new_page);
+                    should_render = true;
+                },
+                Action::Redraw => {
+                    // Force a render even if no page change or keypress occurred
+                    should_render = true;
+                },
+                Action::Exit => break,
+                Action::None => {},
+            }
+
+            if self.stack.is_empty() { 
+                break; 
+            }
+
+            // 4. CONDITIONAL RENDER
+            // Only update the display if something actually changed
+            if should_render {
+                self.render()?;
+            }
+        }
+        Ok(())
+    }
+
+    fn render(&mut self) -> Result<()> {
+        self.display.clear(&self.ctx);
+        
+        if let Some(top_page) = self.stack.last() {
+            top_page.draw(&mut self.display, &self.ctx);
+        }
+        
+        self.display.update()?;
+        Ok(())
+    }
+}
+
+/// The keymap for `layout`. A broken keymap file shouldn't end the
+/// writing session, so its keys are left untranslated instead.
+fn keymap_for(layout: &KeyboardLayout) -> Keymap {
+    Keymap::for_layout(layout).unwrap_or_else(|_| Keymap::us(layout.clone()))
+}
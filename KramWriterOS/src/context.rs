@@ -1,6 +1,42 @@
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+/// The layout printed on the keyboard, which `keymap` translates the
+/// terminal's US keys to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum KeyboardLayout {
     Qwerty,
     Qwertz,
+    Azerty,
+    Dvorak,
+    Colemak,
+    /// A keymap file in the keymaps folder, by name without `.keymap`
+    Custom(String),
+}
+
+impl KeyboardLayout {
+    /// The layouts that don't need a keymap file.
+    pub const BUILT_IN: [KeyboardLayout; 5] = [
+        KeyboardLayout::Qwerty,
+        KeyboardLayout::Qwertz,
+        KeyboardLayout::Azerty,
+        KeyboardLayout::Dvorak,
+        KeyboardLayout::Colemak,
+    ];
+
+    /// The keymap file name without `.keymap`, e.g. "qwertz".
+    pub fn name(&self) -> &str {
+        match self {
+            KeyboardLayout::Qwerty => "qwerty",
+            KeyboardLayout::Qwertz => "qwertz",
+            KeyboardLayout::Azerty => "azerty",
+            KeyboardLayout::Dvorak => "dvorak",
+            KeyboardLayout::Colemak => "colemak",
+            KeyboardLayout::Custom(name) => name,
+        }
+    }
 }
 
 pub struct SystemStatus {
@@ -9,30 +45,60 @@ pub struct SystemStatus {
     pub time_str: String,
 }
 
+impl Default for SystemStatus {
+    fn default() -> Self {
+        Self {
+            wifi_strength: 0,
+            weather_icon: 0,
+            time_str: String::from("00:00"),
+        }
+    }
+}
+
+/// State shared by all pages. Everything but `status` is saved to the
+/// settings file (see `settings`) whenever it changes, so new fields
+/// are persisted as long as they are serializable. Fields missing from
+/// an older file get their value from `Default`.
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
 pub struct Context {
     pub dark_mode: bool,
+    /// IANA name of the local timezone, e.g. "Europe/Berlin"
     pub timezone: String,
     pub layout: KeyboardLayout,
+    #[serde(skip)]
     pub status: SystemStatus,
     // Add these two:
     pub simplenote_email: Option<String>,
-    pub simplenote_token: Option<String>, 
+    pub simplenote_token: Option<String>,
 }
 
 impl Context {
     pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The local timezone, or UTC if `timezone` isn't a known zone.
+    pub fn tz(&self) -> Tz {
+        self.timezone.parse().unwrap_or(Tz::UTC)
+    }
+
+    /// The current local time, for clocks and timestamps.
+    pub fn now(&self) -> DateTime<Tz> {
+        Utc::now().with_timezone(&self.tz())
+    }
+}
+
+impl Default for Context {
+    fn default() -> Self {
         Self {
             dark_mode: false,
             timezone: String::from("UTC"),
             layout: KeyboardLayout::Qwerty,
-            status: SystemStatus {
-                wifi_strength: 0,
-                weather_icon: 0,
-                time_str: String::from("00:00"),
-            },
+            status: SystemStatus::default(),
             // Initialize as None
             simplenote_email: None,
             simplenote_token: None,
         }
     }
-}
\ No newline at end of file
+}
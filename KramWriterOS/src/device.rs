@@ -0,0 +1,59 @@
+//! How this unit is wired and when it goes to sleep.
+//!
+//! Each value is taken from an environment variable or the config file,
+//! like the paths (see `paths`):
+//!
+//! - `disp_pin` (`KRAMWRITER_DISP_PIN`): the GPIO (BCM numbering) wired
+//!   to the display's DISP input, or `none` if DISP is tied high.
+//!   Default `none`.
+//! - `idle_timeout` (`KRAMWRITER_IDLE_TIMEOUT`): seconds without a key
+//!   press before the display is blanked. Default 300.
+//!
+//! Values that can't be parsed get the default.
+
+use crate::paths::config_value;
+use std::env;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+static DEVICE: OnceLock<Device> = OnceLock::new();
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Device {
+    /// GPIO wired to the display's DISP input, None if DISP is tied high
+    pub disp_pin: Option<u8>,
+    /// Blank the display after this long without a key press, so units
+    /// left on don't drain their battery overnight
+    pub idle_timeout: Duration,
+}
+
+impl Default for Device {
+    fn default() -> Self {
+        Self {
+            disp_pin: None,
+            idle_timeout: Duration::from_secs(5 * 60),
+        }
+    }
+}
+
+/// The setup of this unit, resolved from the environment on first use.
+pub fn device() -> &'static Device {
+    DEVICE.get_or_init(|| Device::resolve(|name| env::var(name).ok()))
+}
+
+impl Device {
+    /// Resolves the setup from environment variables (looked up with
+    /// `var`), the config file and the defaults.
+    pub fn resolve(var: impl Fn(&str) -> Option<String>) -> Self {
+        let defaults = Self::default();
+        let disp_pin = match config_value(&var, "disp_pin") {
+            Some(pin) if pin.eq_ignore_ascii_case("none") => None,
+            Some(pin) => pin.parse().ok().or(defaults.disp_pin),
+            None => defaults.disp_pin,
+        };
+        let idle_timeout = config_value(&var, "idle_timeout")
+            .and_then(|seconds| seconds.parse().ok())
+            .map_or(defaults.idle_timeout, Duration::from_secs);
+        Self { disp_pin, idle_timeout }
+    }
+}
@@ -0,0 +1,77 @@
+use rpi_memory_display::{AsyncDisplay, MemoryDisplay, MemoryDisplayBuffer, RppalPin, RppalSpi};
+use embedded_hal::digital::{ErrorType, OutputPin};
+use std::convert::Infallible;
+use std::time::Duration;
+use rppal::spi::{Bus, SlaveSelect};
+use anyhow::Result;
+use super::{Backend, PANEL};
+
+/// The screen often sits unchanged for minutes while writing, so VCOM
+/// is inverted in the background whenever nothing was sent for this long.
+const VCOM_PERIOD: Duration = Duration::from_secs(1);
+
+/// The Sharp memory display on SPI bus 0
+pub struct Hardware {
+    /// Frames are sent by a background thread, so typing never waits
+    /// for SPI
+    inner: AsyncDisplay<RppalSpi, RppalPin, DispPin>,
+}
+
+impl Hardware {
+    /// `disp_pin` is the GPIO wired to the panel's DISP input, or None
+    /// when DISP is tied high and the panel can only be cleared.
+    pub fn new(cs_pin: u8, disp_pin: Option<u8>) -> Result<Self> {
+        let mut inner = MemoryDisplay::new_for_panel(
+            Bus::Spi0,
+            SlaveSelect::Ss0,
+            cs_pin,
+            &PANEL,
+        )?;
+
+        inner.clear()?;
+        let disp = DispPin(disp_pin.map(RppalPin::open).transpose()?);
+        let inner = inner.with_disp(disp)?;
+        let inner = inner.maintain_vcom(VCOM_PERIOD).flush_in_background();
+
+        Ok(Self { inner })
+    }
+}
+
+impl Backend for Hardware {
+    fn present(&mut self, buffer: &mut MemoryDisplayBuffer) -> Result<()> {
+        // Only rows drawn to since the last update are compared and
+        // sent. If the previous frame is still being sent, this one
+        // replaces any frame waiting behind it.
+        self.inner.present(buffer)?;
+        Ok(())
+    }
+
+    fn sleep(&mut self) -> Result<()> {
+        // Let the last frame go out first, so it isn't skipped halfway
+        self.inner.wait()?;
+        self.inner.lock().sleep()?;
+        Ok(())
+    }
+
+    fn wake(&mut self) -> Result<()> {
+        self.inner.lock().wake()?;
+        Ok(())
+    }
+}
+
+/// The DISP pin, if it is wired to a GPIO at all
+struct DispPin(Option<RppalPin>);
+
+impl ErrorType for DispPin {
+    type Error = Infallible;
+}
+
+impl OutputPin for DispPin {
+    fn set_low(&mut self) -> std::result::Result<(), Infallible> {
+        self.0.as_mut().map_or(Ok(()), |pin| pin.set_low())
+    }
+
+    fn set_high(&mut self) -> std::result::Result<(), Infallible> {
+        self.0.as_mut().map_or(Ok(()), |pin| pin.set_high())
+    }
+}
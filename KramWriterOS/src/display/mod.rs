@@ -0,0 +1,178 @@
+#[cfg(feature = "hardware")]
+mod hardware;
+mod simulator;
+
+use rpi_memory_display::{Image, MemoryDisplayBuffer, Pixel, Rotation};
+use rpi_memory_display::panel::Panel;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+use anyhow::Result;
+use crate::context::Context;
+use crate::ui::bitmap::Bitmap;
+use embedded_graphics::mono_font::{iso_8859_1, MonoFont, MonoTextStyle};
+use embedded_graphics::prelude::{Drawable, Point};
+use embedded_graphics::text::{Baseline, Text};
+
+#[cfg(feature = "hardware")]
+pub use hardware::Hardware;
+pub use simulator::{FrameDirectory, Headless, Terminal};
+
+const PANEL: Panel = Panel::LS027B7DH01;
+/// Font for `draw_text`, compiled in
+pub const BUILT_IN_FONT: &MonoFont = &iso_8859_1::FONT_6X10;
+/// Orientation of the panel in the case. Use Deg180 when the panel's
+/// connector ends up on the other side, Deg90/Deg270 for portrait.
+const ROTATION: Rotation = Rotation::Deg0;
+
+/// Where finished frames are shown: the panel itself or a simulator.
+pub trait Backend {
+    /// Shows the dirty rows of `buffer` and marks it clean. Should
+    /// return quickly, so typing doesn't wait for the screen.
+    fn present(&mut self, buffer: &mut MemoryDisplayBuffer) -> Result<()>;
+
+    /// Blanks the screen to save power.
+    fn sleep(&mut self) -> Result<()>;
+
+    /// Turns the screen back on. The whole buffer is presented next.
+    fn wake(&mut self) -> Result<()>;
+}
+
+/// The screen the pages draw on. Frames are kept in a buffer and
+/// handed to the backend by `update`.
+pub struct SharpDisplay {
+    backend: Box<dyn Backend>,
+    buffer: MemoryDisplayBuffer,
+    asleep: bool,
+}
+
+impl SharpDisplay {
+    pub fn new(backend: Box<dyn Backend>) -> Self {
+        let mut buffer = PANEL.buffer();
+        buffer.set_rotation(ROTATION);
+        Self { backend, buffer, asleep: false }
+    }
+
+    /// Width of the screen as seen by the pages, after rotation.
+    pub fn width(&self) -> usize {
+        self.buffer.width()
+    }
+
+    /// Height of the screen as seen by the pages, after rotation.
+    pub fn height(&self) -> usize {
+        self.buffer.height()
+    }
+
+    pub fn clear(&mut self, ctx: &Context) {
+        // If dark mode is on, we fill the background with Black.
+        // If dark mode is off, we fill with White.
+        let background_color = if ctx.dark_mode { 
+            Pixel::Black 
+        } else { 
+            Pixel::White 
+        };
+        
+        // This assumes your buffer is what actually holds the pixels
+        self.buffer.fill(background_color); 
+    }
+
+    pub fn update(&mut self) -> Result<()> {
+        self.backend.present(&mut self.buffer)
+    }
+
+    /// Blanks the screen to save power. The panel is cleared and
+    /// turned off, and VCOM inversion stops until `wake`.
+    pub fn sleep(&mut self) -> Result<()> {
+        self.backend.sleep()?;
+        self.asleep = true;
+        Ok(())
+    }
+
+    /// Turns the screen back on. It was cleared by `sleep`, so the
+    /// next `update` sends the whole buffer.
+    pub fn wake(&mut self) -> Result<()> {
+        self.backend.wake()?;
+        self.buffer.mark_all_dirty();
+        self.asleep = false;
+        Ok(())
+    }
+
+    pub fn is_asleep(&self) -> bool {
+        self.asleep
+    }
+
+    /// The frame as drawn so far, e.g. for comparing with snapshots
+    pub fn buffer(&self) -> &MemoryDisplayBuffer {
+        &self.buffer
+    }
+
+    /// Saves what is on screen as a PNG for bug reports and docs
+    pub fn save_screenshot(&self, path: &Path) -> Result<()> {
+        self.buffer.write_png(BufWriter::new(File::create(path)?))?;
+        Ok(())
+    }
+
+    /// The "Smart" draw pixel that looks at global context for Dark Mode
+    pub fn draw_pixel(&mut self, x: usize, y: usize, pixel: Pixel, ctx: &Context) {
+        if self.buffer.contains(x, y) {
+            self.buffer.set_pixel(x, y, ink(pixel, ctx));
+        }
+    }
+
+    /// Fills a rectangle, clipped to the screen, honouring dark mode
+    pub fn fill_rect(&mut self, x: usize, y: usize, width: usize, height: usize, pixel: Pixel, ctx: &Context) {
+        self.buffer.fill_rect(x, y, width, height, ink(pixel, ctx));
+    }
+
+    /// Copies a whole bitmap, white pixels included, except where its
+    /// image is masked out. It may hang over the edges of the screen.
+    pub fn draw_bitmap(&mut self, x: i32, y: i32, bmp: &Bitmap, ctx: &Context) {
+        if ctx.dark_mode {
+            self.buffer.blit(x as isize, y as isize, &inverted(&bmp.image));
+        } else {
+            self.buffer.blit(x as isize, y as isize, &bmp.image);
+        }
+    }
+
+    /// Draws only the black pixels of a bitmap, in `color`. White
+    /// pixels are transparent. It may hang over the edges of the screen.
+    pub fn draw_bitmap_colored(&mut self, x: i32, y: i32, bmp: &Bitmap, color: Pixel, ctx: &Context) {
+        self.draw_image_colored(x, y, &bmp.image, color, ctx);
+    }
+
+    /// Like `draw_bitmap_colored`, for an image, e.g. a glyph.
+    pub fn draw_image_colored(&mut self, x: i32, y: i32, image: &Image, color: Pixel, ctx: &Context) {
+        self.buffer.stamp(x as isize, y as isize, image, ink(color, ctx));
+    }
+
+    /// Draws text in the built-in 6x10 Latin-1 font, with `(x, y)` the
+    /// top left corner. Needs no font files, so it works for error
+    /// and boot messages. Other characters are drawn as "?".
+    pub fn draw_text(&mut self, x: usize, y: usize, text: &str, ctx: &Context) {
+        let style = MonoTextStyle::new(BUILT_IN_FONT, ink(Pixel::Black, ctx).into());
+        let origin = Point::new(x as i32, y as i32);
+        // Drawing to the buffer can't fail
+        let _ = Text::with_baseline(text, origin, style, Baseline::Top).draw(&mut self.buffer);
+    }
+}
+
+/// `image` with black and white swapped, keeping its mask so that
+/// the pixels around it stay as they are.
+fn inverted(image: &Image) -> Image {
+    let data = image.as_ref().iter().map(|byte| !byte).collect();
+    let inverted = Image::from_bytes(image.width(), image.height(), data);
+    match image.mask() {
+        Some(mask) => inverted.with_mask(mask.to_vec()),
+        None => inverted,
+    }
+}
+
+/// If dark mode is on, we invert the logic:
+/// What was supposed to be Black (ink) becomes White (paper)
+fn ink(pixel: Pixel, ctx: &Context) -> Pixel {
+    match (ctx.dark_mode, pixel) {
+        (false, pixel) => pixel,
+        (true, Pixel::Black) => Pixel::White,
+        (true, Pixel::White) => Pixel::Black,
+    }
+}
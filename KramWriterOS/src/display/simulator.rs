@@ -0,0 +1,120 @@
+//! Display backends for running KramWriterOS without the panel, e.g.
+//! on a dev laptop or in tests.
+
+use rpi_memory_display::{MemoryDisplayBuffer, Pixel};
+use std::fs::{self, File};
+use std::io::{stdout, BufWriter, Stdout, Write};
+use std::path::PathBuf;
+use termion::cursor::{self, HideCursor};
+use termion::screen::{AlternateScreen, IntoAlternateScreen};
+use anyhow::Result;
+use super::Backend;
+
+/// Draws the screen in the terminal, two pixel rows per character
+/// using half blocks. Black pixels are drawn in the terminal's text
+/// colour. A 400x240 panel needs a 400x120 terminal.
+pub struct Terminal {
+    out: HideCursor<AlternateScreen<Stdout>>,
+    /// What each terminal line shows, so only changed lines are redrawn
+    lines: Vec<String>,
+}
+
+impl Terminal {
+    pub fn new() -> Result<Self> {
+        let out = HideCursor::from(stdout().into_alternate_screen()?);
+        Ok(Self { out, lines: Vec::new() })
+    }
+}
+
+impl Backend for Terminal {
+    fn present(&mut self, buffer: &mut MemoryDisplayBuffer) -> Result<()> {
+        let black = |x, y| y < buffer.height() && buffer.get_pixel(x, y) == Pixel::Black;
+        let height = buffer.height().div_ceil(2);
+        self.lines.resize(height, String::new());
+        for (row, shown) in self.lines.iter_mut().enumerate() {
+            let y = row * 2;
+            let line: String = (0..buffer.width())
+                .map(|x| match (black(x, y), black(x, y + 1)) {
+                    (true, true) => '█',
+                    (true, false) => '▀',
+                    (false, true) => '▄',
+                    (false, false) => ' ',
+                })
+                .collect();
+            if *shown != line {
+                write!(self.out, "{}{}", cursor::Goto(1, row as u16 + 1), line)?;
+                *shown = line;
+            }
+        }
+        self.out.flush()?;
+        buffer.mark_clean();
+        Ok(())
+    }
+
+    fn sleep(&mut self) -> Result<()> {
+        write!(self.out, "{}", termion::clear::All)?;
+        self.out.flush()?;
+        self.lines.clear();
+        Ok(())
+    }
+
+    fn wake(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Saves every changed frame as a numbered PNG in a directory, for
+/// looking at later or turning into a video.
+pub struct FrameDirectory {
+    dir: PathBuf,
+    count: usize,
+    last: Option<MemoryDisplayBuffer>,
+}
+
+impl FrameDirectory {
+    pub fn new(dir: PathBuf) -> Result<Self> {
+        fs::create_dir_all(&dir)?;
+        Ok(Self { dir, count: 0, last: None })
+    }
+}
+
+impl Backend for FrameDirectory {
+    fn present(&mut self, buffer: &mut MemoryDisplayBuffer) -> Result<()> {
+        buffer.mark_clean();
+        if self.last.as_ref() == Some(buffer) {
+            return Ok(());
+        }
+        self.count += 1;
+        let path = self.dir.join(format!("frame-{:06}.png", self.count));
+        buffer.write_png(BufWriter::new(File::create(path)?))?;
+        self.last = Some(buffer.clone());
+        Ok(())
+    }
+
+    fn sleep(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn wake(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Shows frames nowhere. Tests read the frame from
+/// `SharpDisplay::buffer` instead.
+pub struct Headless;
+
+impl Backend for Headless {
+    fn present(&mut self, buffer: &mut MemoryDisplayBuffer) -> Result<()> {
+        buffer.mark_clean();
+        Ok(())
+    }
+
+    fn sleep(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn wake(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
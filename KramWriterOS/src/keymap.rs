@@ -0,0 +1,124 @@
+//! Translating key presses for the keyboard's layout.
+//!
+//! The terminal reports keys as if the keyboard were US QWERTY. A
+//! Keymap turns those into what the keys type on the layout chosen in
+//! the settings, before global shortcuts and pages see them.
+//!
+//! Layouts are keymap files. The built-in ones are in `keymaps/` and
+//! compiled in; files in the data directory's `keymaps` folder add
+//! more layouts, or replace a built-in one with the same name. Each
+//! line of a keymap file holds two strings of equal length: characters
+//! the US layout puts on some keys, and what the same keys type on
+//! this layout. Lines starting with `#` are comments:
+//!
+//! ```text
+//! # QWERTZ swaps y and z
+//! yzYZ    zyZY
+//! ```
+
+use crate::context::KeyboardLayout;
+use crate::paths::paths;
+use anyhow::{bail, Context as _, Result};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use termion::event::Key;
+
+/// Keymap files compiled into the binary, by layout name.
+const BUILT_IN: [(&str, &str); 4] = [
+    ("qwertz", include_str!("../keymaps/qwertz.keymap")),
+    ("azerty", include_str!("../keymaps/azerty.keymap")),
+    ("dvorak", include_str!("../keymaps/dvorak.keymap")),
+    ("colemak", include_str!("../keymaps/colemak.keymap")),
+];
+
+/// Maps characters sent for a US keyboard to those of a layout.
+/// Characters without a mapping are passed through.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Keymap {
+    layout: KeyboardLayout,
+    chars: HashMap<char, char>,
+}
+
+impl Keymap {
+    /// The keymap for `layout`: its file in the keymaps folder, else the
+    /// built-in one. Layouts without either leave keys untranslated.
+    /// Fails if the file can't be read or parsed.
+    pub fn for_layout(layout: &KeyboardLayout) -> Result<Self> {
+        let file = paths().keymaps().join(format!("{}.keymap", layout.name()));
+        if file.exists() {
+            return Self::load(&file, layout.clone());
+        }
+        match BUILT_IN.iter().find(|(name, _)| *name == layout.name()) {
+            Some((_, text)) => Self::parse(text, layout.clone()),
+            None => Ok(Self::us(layout.clone())),
+        }
+    }
+
+    /// Reads a keymap file for `layout`.
+    pub fn load(path: &Path, layout: KeyboardLayout) -> Result<Self> {
+        let text = fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+        Self::parse(&text, layout).with_context(|| path.display().to_string())
+    }
+
+    /// Parses the contents of a keymap file for `layout`.
+    pub fn parse(text: &str, layout: KeyboardLayout) -> Result<Self> {
+        let mut chars = HashMap::new();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let columns: Vec<&str> = line.split_whitespace().collect();
+            let [us, typed] = columns[..] else {
+                bail!("line {}: expected two columns, found {}", number + 1, columns.len());
+            };
+            if us.chars().count() != typed.chars().count() {
+                bail!("line {}: columns have different lengths", number + 1);
+            }
+            chars.extend(us.chars().zip(typed.chars()));
+        }
+        Ok(Self { layout, chars })
+    }
+
+    /// Leaves keys as the terminal sends them, e.g. when the keymap for
+    /// `layout` is broken.
+    pub fn us(layout: KeyboardLayout) -> Self {
+        Self { layout, chars: HashMap::new() }
+    }
+
+    /// The layout this keymap was made for.
+    pub fn layout(&self) -> &KeyboardLayout {
+        &self.layout
+    }
+
+    /// Translates a key press. Ctrl and Alt shortcuts follow the letters
+    /// printed on the keys, so Ctrl-z is the key labelled Z.
+    pub fn translate(&self, key: Key) -> Key {
+        let map = |c: char| self.chars.get(&c).copied().unwrap_or(c);
+        match key {
+            Key::Char(c) => Key::Char(map(c)),
+            Key::Ctrl(c) if map(c).is_ascii_alphabetic() => Key::Ctrl(map(c)),
+            Key::Alt(c) => Key::Alt(map(c)),
+            key => key,
+        }
+    }
+}
+
+/// The built-in layouts, then the ones added by files in the keymaps
+/// folder, in the order the settings page cycles through them.
+pub fn layouts() -> Vec<KeyboardLayout> {
+    let mut layouts = KeyboardLayout::BUILT_IN.to_vec();
+    let mut custom: Vec<String> = fs::read_dir(paths().keymaps())
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "keymap"))
+        .filter_map(|path| Some(path.file_stem()?.to_string_lossy().into_owned()))
+        .filter(|name| !layouts.iter().any(|layout| layout.name() == name))
+        .collect();
+    custom.sort();
+    layouts.extend(custom.into_iter().map(KeyboardLayout::Custom));
+    layouts
+}
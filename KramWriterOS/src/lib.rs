@@ -0,0 +1,9 @@
+pub mod app;
+pub mod context;
+pub mod device;
+pub mod display;
+pub mod keymap;
+pub mod pages;
+pub mod paths;
+pub mod settings;
+pub mod ui;
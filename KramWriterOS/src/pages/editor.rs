@@ -3,32 +3,24 @@ use crate::context::Context;
 use crate::display::SharpDisplay;
 use crate::ui::bitmap::Bitmap;
 use crate::ui::fonts::FontRenderer;
+use crate::ui::layout::{Align, TextLayout};
+use crate::paths::paths;
 use termion::event::Key;
 use rpi_memory_display::Pixel;
 use std::path::PathBuf;
 use std::fs;
 
-// Timezone and Time imports
-use chrono::{Utc, FixedOffset}; 
-
-// --- LAYOUT STRUCTURE ---
-#[derive(Debug, Clone)]
-struct VisualLine {
-    text: String,       
-    start_index: usize, // Byte index in the main content
-    len: usize,         // Byte length
-    is_hard_break: bool,
-}
-
-impl VisualLine {
-    fn end_index(&self) -> usize {
-        self.start_index + self.len
-    }
-}
+/// Width of the text column, leaving room for the scrollbar
+const TEXT_WIDTH: i32 = 370;
 
 pub struct EditorPage {
     path: PathBuf,
     content: String,
+    /// `content` broken into lines for the current font size
+    layout: TextLayout,
+    /// Set wherever `content` or the font size changes, so `update`
+    /// lays the text out again
+    layout_dirty: bool,
     cursor_pos: usize, // Tracks byte offset
     scroll_line_offset: usize,
     target_cursor_x: Option<i32>, 
@@ -44,22 +36,26 @@ pub struct EditorPage {
 
 impl EditorPage {
     pub fn new(path: PathBuf) -> Self {
-        let renderer = FontRenderer::new("/home/kramwriter/KramWriter/fonts/Inter_28pt-Medium.ttf");
-        let ui_renderer = FontRenderer::new("/home/kramwriter/KramWriter/fonts/BebasNeue-Regular.ttf");
-        let asset_path = "/home/kramwriter/KramWriter/assets/Writing";
+        let renderer = FontRenderer::chain(&paths().text_fonts());
+        let ui_renderer = FontRenderer::new(&paths().font("BebasNeue-Regular.ttf"));
+        let asset_path = paths().asset("Writing");
         let content = fs::read_to_string(&path).unwrap_or_default();
         let len = content.len();
+        let font_size = 22.0;
+        let layout = build_layout(&content, &renderer, font_size);
 
         Self {
             path,
             content,
+            layout,
+            layout_dirty: false,
             cursor_pos: len,
             scroll_line_offset: 0,
             target_cursor_x: None,
             is_dirty: false,
             renderer,
             ui_renderer,
-            font_size: 22.0,
+            font_size,
             top_margin: 10,
             save_icons: [
                 Bitmap::load(&format!("{}/save_0.bmp", asset_path)).ok(),
@@ -82,116 +78,16 @@ impl EditorPage {
         }
     }
 
-    fn cursor_is_on_this_line(&self, line: &VisualLine) -> bool {
-        if self.cursor_pos >= line.start_index && self.cursor_pos < line.end_index() {
-            true
-        } else if self.cursor_pos == line.end_index() {
-            line.is_hard_break || line.len == 0 || self.cursor_pos == self.content.len()
-        } else {
-            false
-        }
-    }
-
-    fn build_layout(&self, max_width: f32) -> Vec<VisualLine> {
-        let mut visual_lines = Vec::new();
-        let mut current_abs_index = 0;
-        let paragraphs: Vec<&str> = self.content.split('\n').collect();
-        let total_paras = paragraphs.len();
-
-        for (i, para) in paragraphs.iter().enumerate() {
-            let is_last_para = i == total_paras - 1;
-            
-            if para.is_empty() {
-                visual_lines.push(VisualLine {
-                    text: String::new(),
-                    start_index: current_abs_index,
-                    len: 0,
-                    is_hard_break: !is_last_para,
-                });
-                current_abs_index += 1;
-                continue;
-            }
-
-            let mut current_line = String::new();
-            let mut line_start_rel = 0;
-            
-            for word in para.split_inclusive(' ') {
-                let test_line = format!("{}{}", current_line, word);
-                let w = self.renderer.calculate_width(&test_line, self.font_size);
-                
-                if (w as f32) > max_width && !current_line.is_empty() {
-                    let len = current_line.len();
-                    visual_lines.push(VisualLine {
-                        text: current_line,
-                        start_index: current_abs_index + line_start_rel,
-                        len,
-                        is_hard_break: false,
-                    });
-                    line_start_rel += len;
-                    current_line = word.to_string();
-                } else {
-                    current_line = test_line;
-                }
-            }
-
-            let len = current_line.len();
-            visual_lines.push(VisualLine {
-                text: current_line,
-                start_index: current_abs_index + line_start_rel,
-                len,
-                is_hard_break: !is_last_para,
-            });
-
-            current_abs_index += para.len();
-            if !is_last_para { current_abs_index += 1; }
-        }
-        visual_lines
-    }
-
-    fn move_cursor_vertical(&mut self, direction: i32, layout: &[VisualLine]) {
-        let current_line_idx = layout.iter().position(|l| self.cursor_is_on_this_line(l));
-
-        if let Some(idx) = current_line_idx {
-            let next_idx = idx as i32 + direction;
-            if next_idx >= 0 && next_idx < layout.len() as i32 {
-                let current_line = &layout[idx];
-                let target_line = &layout[next_idx as usize];
-
-                let target_x = if let Some(tx) = self.target_cursor_x {
-                    tx
-                } else {
-                    let offset = self.cursor_pos.saturating_sub(current_line.start_index);
-                    // SAFE SLICE: find the nearest char boundary
-                    let safe_offset = self.get_safe_boundary(&current_line.text, offset);
-                    let text_before = &current_line.text[..safe_offset];
-                    self.renderer.calculate_width(text_before, self.font_size) as i32
-                };
-
-                let mut best_offset = 0;
-                let mut min_diff = i32::MAX;
-                
-                // Iterate through actual character boundaries
-                for (byte_idx, _) in target_line.text.char_indices().chain(std::iter::once((target_line.text.len(), ' '))) {
-                    let sub = &target_line.text[..byte_idx];
-                    let w = self.renderer.calculate_width(sub, self.font_size) as i32;
-                    let diff = (w - target_x).abs();
-                    if diff < min_diff {
-                        min_diff = diff;
-                        best_offset = byte_idx;
-                    }
-                }
-                self.cursor_pos = target_line.start_index + best_offset;
-                self.target_cursor_x = Some(target_x);
-            }
-        }
-    }
-
-    fn get_safe_boundary(&self, s: &str, byte_idx: usize) -> usize {
-        let mut idx = byte_idx.min(s.len());
-        while !s.is_char_boundary(idx) && idx > 0 {
-            idx -= 1;
+    fn move_cursor_vertical(&mut self, direction: i32) {
+        let layout = &self.layout;
+        let current_line = layout.line_of(self.cursor_pos);
+        let Some(target_line) = current_line.checked_add_signed(direction as isize) else { return };
+        if target_line >= layout.lines().len() {
+            return;
         }
-        idx
+        let target_x = self.target_cursor_x.unwrap_or_else(|| layout.position(&self.content, self.cursor_pos).0);
+        self.cursor_pos = layout.cursor_in_line(&self.content, target_line, target_x);
+        self.target_cursor_x = Some(target_x);
     }
 
     fn get_word_count(&self) -> usize {
@@ -203,7 +99,7 @@ impl EditorPage {
         let y_text = y_start as i32 + 18;
         let ui_size = 18.0; 
 
-        for x in 0..400 { display.draw_pixel(x, y_start, Pixel::Black, ctx); }
+        display.fill_rect(0, y_start, display.width(), 1, Pixel::Black, ctx);
         
         let save_icon = if self.is_dirty { &self.save_icons[0] } else { &self.save_icons[1] };
         if let Some(bmp) = save_icon { self.draw_icon(display, bmp, 5, y_start + 3, ctx); }
@@ -216,15 +112,7 @@ impl EditorPage {
         let w_count = format!("W:{}", self.get_word_count());
         self.ui_renderer.draw_text_colored(display, &w_count, 180, y_text, ui_size, Pixel::Black, ctx);
         
-        let offset_hours = ctx.timezone.parse::<f32>().unwrap_or(0.0);
-        let offset_seconds = (offset_hours * 3600.0) as i32;
-        
-        let time_str = if let Some(offset) = FixedOffset::east_opt(offset_seconds) {
-            let now = Utc::now().with_timezone(&offset);
-            now.format("%H:%M").to_string()
-        } else {
-            Utc::now().format("%H:%M").to_string()
-        };
+        let time_str = ctx.now().format("%H:%M").to_string();
         
         self.ui_renderer.draw_text_colored(display, &time_str, 305, y_text, ui_size, Pixel::Black, ctx);
         
@@ -236,15 +124,7 @@ impl EditorPage {
     }
 
     fn draw_icon(&self, display: &mut SharpDisplay, bmp: &Bitmap, x_off: usize, y_off: usize, ctx: &Context) {
-        for y in 0..bmp.height {
-            for x in 0..bmp.width {
-                if bmp.pixels[y * bmp.width + x] == Pixel::Black {
-                    let sx = x + x_off;
-                    let sy = y + y_off;
-                    if sx < 400 && sy < 240 { display.draw_pixel(sx, sy, Pixel::Black, ctx); }
-                }
-            }
-        }
+        display.draw_bitmap_colored(x_off as i32, y_off as i32, bmp, Pixel::Black, ctx);
     }
 
     fn draw_scrollbar(&self, display: &mut SharpDisplay, total_lines: usize, visible_count: usize, ctx: &Context) {
@@ -255,17 +135,12 @@ impl EditorPage {
         let thumb_h = (((visible_count as f32 / total_lines as f32) * track_h as f32) as i32).max(10);
         let scrollable_dist = (total_lines - visible_count) as f32;
         let thumb_y = track_top + ((self.scroll_line_offset as f32 / scrollable_dist) * (track_h - thumb_h) as f32) as i32;
-        for y in thumb_y..(thumb_y + thumb_h) {
-            for x in 395..399 { display.draw_pixel(x, y as usize, Pixel::Black, ctx); }
-        }
+        display.fill_rect(395, thumb_y as usize, 4, thumb_h as usize, Pixel::Black, ctx);
     }
 }
 
 impl Page for EditorPage {
     fn update(&mut self, key: Key, _ctx: &mut Context) -> Action {
-        let max_width = 370.0;
-        let layout = self.build_layout(max_width);
-
         match key {
             Key::Esc => return Action::Pop,
             Key::Ctrl('s') => { let _ = fs::write(&self.path, &self.content); self.is_dirty = false; }
@@ -273,8 +148,8 @@ impl Page for EditorPage {
             // --- UPDATED FONT SIZE HOTKEYS ---
             // Termion often handles Alt+Arrow by returning standard keys if the terminal is in raw mode.
             // Some environments map them to specific Char or specialized codes.
-            Key::Alt('k') | Key::Up if matches!(key, Key::Alt(_)) => { if self.font_size < 60.0 { self.font_size += 2.0; } }
-            Key::Alt('j') | Key::Down if matches!(key, Key::Alt(_)) => { if self.font_size > 10.0 { self.font_size -= 2.0; } }
+            Key::Alt('k') | Key::Up if matches!(key, Key::Alt(_)) => { if self.font_size < 60.0 { self.font_size += 2.0; self.layout_dirty = true; } }
+            Key::Alt('j') | Key::Down if matches!(key, Key::Alt(_)) => { if self.font_size > 10.0 { self.font_size -= 2.0; self.layout_dirty = true; } }
             
             // Note: Standard termion::event::Key doesn't have Alt(Up/Down). 
             // If the above doesn't trigger, use Alt + and Alt - as fallbacks or check raw escape codes.
@@ -297,12 +172,13 @@ impl Page for EditorPage {
                 } 
                 self.target_cursor_x = None; 
             }
-            Key::Up => self.move_cursor_vertical(-1, &layout),
-            Key::Down => self.move_cursor_vertical(1, &layout),
+            Key::Up => self.move_cursor_vertical(-1),
+            Key::Down => self.move_cursor_vertical(1),
             Key::Char(c) => {
                 self.content.insert(self.cursor_pos, c);
                 self.cursor_pos += c.len_utf8();
                 self.is_dirty = true;
+                self.layout_dirty = true;
                 self.target_cursor_x = None;
             }
             Key::Backspace => {
@@ -311,6 +187,7 @@ impl Page for EditorPage {
                         self.content.remove(idx);
                         self.cursor_pos = idx;
                         self.is_dirty = true;
+                        self.layout_dirty = true;
                         self.target_cursor_x = None;
                     }
                 }
@@ -318,11 +195,14 @@ impl Page for EditorPage {
             _ => {}
         }
 
-        // Re-calculate layout to handle scrolling
-        let layout = self.build_layout(max_width); 
-        let line_height = (self.font_size * 1.2) as i32;
-        let visible_lines = ((218 - self.top_margin) / line_height) as usize;
-        let current_line_idx = layout.iter().position(|l| self.cursor_is_on_this_line(l)).unwrap_or(0);
+        if self.layout_dirty {
+            self.layout = build_layout(&self.content, &self.renderer, self.font_size);
+            self.layout_dirty = false;
+        }
+
+        // Scroll to keep the cursor visible
+        let visible_lines = ((218 - self.top_margin) / self.layout.line_height()) as usize;
+        let current_line_idx = self.layout.line_of(self.cursor_pos);
 
         if current_line_idx < self.scroll_line_offset {
             self.scroll_line_offset = current_line_idx;
@@ -335,39 +215,31 @@ impl Page for EditorPage {
 
     fn draw(&self, display: &mut SharpDisplay, ctx: &Context) {
         let margin = 10;
-        let max_width = 370.0;
-        let layout = self.build_layout(max_width);
-        let line_height = (self.font_size * 1.2) as i32;
-        let visible_lines = ((218 - self.top_margin) / line_height) as usize;
-
-        let mut draw_y = self.top_margin;
-
-        for (_idx, line) in layout.iter().enumerate().skip(self.scroll_line_offset) {
-            if draw_y + line_height > 218 { break; }
-
-            if !line.text.is_empty() {
-                self.renderer.draw_text_colored(display, &line.text, margin, draw_y + (self.font_size as i32), self.font_size, Pixel::Black, ctx);
-            }
-
-            if self.cursor_is_on_this_line(line) {
-                let offset = self.cursor_pos.saturating_sub(line.start_index);
-                // SAFE SLICE: Find the nearest char boundary for the cursor position
-                let safe_offset = self.get_safe_boundary(&line.text, offset);
-                let sub_text = &line.text[..safe_offset];
-                let cursor_x = margin + self.renderer.calculate_width(sub_text, self.font_size) as i32;
-                
-                let cursor_height = self.font_size as i32;
-                for cy in draw_y..(draw_y + cursor_height) {
-                    if cy < 218 && cursor_x < 398 {
-                        display.draw_pixel(cursor_x as usize, cy as usize, Pixel::Black, ctx);
-                        display.draw_pixel((cursor_x + 1) as usize, cy as usize, Pixel::Black, ctx);
-                    }
+        let layout = &self.layout;
+        let visible_lines = ((218 - self.top_margin) / layout.line_height()) as usize;
+
+        let shown = self.scroll_line_offset..self.scroll_line_offset + visible_lines;
+        layout.draw_lines(&self.content, display, shown.clone(), margin, self.top_margin, ctx);
+
+        let line = layout.line_of(self.cursor_pos);
+        if shown.contains(&line) {
+            let (x, y) = layout.position(&self.content, self.cursor_pos);
+            let cursor_x = margin + x;
+            let draw_y = self.top_margin + y - self.scroll_line_offset as i32 * layout.line_height();
+            let cursor_height = self.font_size as i32;
+            for cy in draw_y..(draw_y + cursor_height) {
+                if cy < 218 && cursor_x < 398 {
+                    display.draw_pixel(cursor_x as usize, cy as usize, Pixel::Black, ctx);
+                    display.draw_pixel((cursor_x + 1) as usize, cy as usize, Pixel::Black, ctx);
                 }
             }
-            draw_y += line_height;
         }
 
-        self.draw_scrollbar(display, layout.len(), visible_lines, ctx);
+        self.draw_scrollbar(display, layout.lines().len(), visible_lines, ctx);
         self.draw_bottom_bar(display, ctx);
     }
-}
\ No newline at end of file
+}
+
+fn build_layout(content: &str, renderer: &FontRenderer, font_size: f32) -> TextLayout {
+    TextLayout::new(content, renderer, font_size, TEXT_WIDTH, Align::Left)
+}
@@ -6,9 +6,10 @@ use crate::ui::fonts::FontRenderer;
 use termion::event::Key;
 use rpi_memory_display::Pixel;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use crate::pages::name_entry::NameEntryPage;
 use crate::pages::editor::EditorPage;
+use crate::paths::paths;
 
 #[derive(PartialEq)]
 pub enum BrowserFocus {
@@ -51,8 +52,8 @@ pub struct FileBrowserPage {
 
 impl FileBrowserPage {
     pub fn new(mode: BrowserMode) -> Self {
-        let renderer = FontRenderer::new("/home/kramwriter/KramWriter/fonts/BebasNeue-Regular.ttf");
-        let asset_path = "/home/kramwriter/KramWriter/assets/FileBrowser";
+        let renderer = FontRenderer::new(&paths().font("BebasNeue-Regular.ttf"));
+        let asset_path = paths().asset("FileBrowser");
         
         let footer_full = [
             Bitmap::load(&format!("{}/bottom_bar_3.bmp", asset_path)).ok(),
@@ -75,7 +76,7 @@ impl FileBrowserPage {
             footer_full,
             footer_open,
             renderer,
-            current_directory: PathBuf::from("/home/kramwriter/folder"),
+            current_directory: paths().documents.clone(),
             entries: Vec::new(),
             selected_index: 0,
             scroll_offset: 0,
@@ -91,7 +92,6 @@ impl FileBrowserPage {
 
     fn refresh_entries(&mut self) {
         self.entries.clear();
-        let home_base = "/home/kramwriter";
         let current_str = self.current_directory.to_string_lossy().to_string();
 
         // 1. CLEANUP LOGIC: If we are in the simplenote folder, check for orphaned files
@@ -102,7 +102,8 @@ impl FileBrowserPage {
              // For now, we will focus on ensuring the list reflects the actual FS state.
         }
 
-        if current_str.len() > home_base.len() {
+        // The documents folder is the top, there is no ".." above it
+        if self.current_directory != paths().documents {
             if let Some(parent) = self.current_directory.parent() {
                 self.entries.push(FileEntry {
                     name: String::from(".."),
@@ -150,11 +151,7 @@ impl FileBrowserPage {
         let draw_color = if is_selected { Pixel::White } else { Pixel::Black };
         
         if is_selected {
-            for sy in y..(y + row_height) {
-                for sx in 0..400 {
-                    display.draw_pixel(sx as usize, sy as usize, Pixel::Black, ctx);
-                }
-            }
+            display.fill_rect(0, y as usize, display.width(), row_height as usize, Pixel::Black, ctx);
         }
 
         let icon = if entry.name == ".." { &self.back_icon } 
@@ -178,22 +175,14 @@ impl FileBrowserPage {
     }
 
     fn draw_icon_colored(&self, display: &mut SharpDisplay, bmp: &Bitmap, x_off: usize, y_off: usize, color: Pixel, ctx: &Context) {
-        for y in 0..bmp.height {
-            for x in 0..bmp.width {
-                if bmp.pixels[y * bmp.width + x] == Pixel::Black {
-                    let sx = x + x_off;
-                    let sy = y + y_off;
-                    if sx < 400 && sy < 240 {
-                        display.draw_pixel(sx, sy, color, ctx);
-                    }
-                }
-            }
-        }
+        display.draw_bitmap_colored(x_off as i32, y_off as i32, bmp, color, ctx);
     }
 
     fn format_header_path(&self) -> String {
-        let full_path = self.current_directory.to_string_lossy().to_string();
-        let mut display_path = full_path.replacen("/home/kramwriter", "", 1);
+        // Shown from the documents folder's name down, e.g. "/FOLDER/NOTES"
+        let base = paths().documents.parent().unwrap_or(Path::new("/"));
+        let relative = self.current_directory.strip_prefix(base).unwrap_or(&self.current_directory);
+        let mut display_path = format!("/{}", relative.to_string_lossy());
         if display_path.is_empty() || display_path == "/" { display_path = String::from(""); }
         display_path = display_path.to_uppercase();
         if display_path.len() > 30 {
@@ -298,7 +287,7 @@ impl Page for FileBrowserPage {
 
     fn draw(&self, display: &mut SharpDisplay, ctx: &Context) {
         display.clear(ctx); // Added to ensure visual clarity during animations
-        for x in 0..400 { display.draw_pixel(x, 22, Pixel::Black, ctx); }
+        for x in 0..display.width() { display.draw_pixel(x, 22, Pixel::Black, ctx); }
         if let Some(bmp) = &self.home_icon { self.draw_icon_colored(display, bmp, 2, 2, Pixel::Black, ctx); }
         let header_path = self.format_header_path();
         self.renderer.draw_text_colored(display, &header_path, 35, 18, 20.0, Pixel::Black, ctx);
@@ -320,17 +309,7 @@ impl Page for FileBrowserPage {
         };
 
         if let Some(bmp) = footer_bmp {
-            let y_start = 216; 
-            for y in 0..bmp.height {
-                let sy = y as i32 + y_start;
-                if sy < 240 {
-                    for x in 0..bmp.width.min(400) {
-                        if bmp.pixels[y * bmp.width + x] == Pixel::Black {
-                            display.draw_pixel(x, sy as usize, Pixel::Black, ctx);
-                        }
-                    }
-                }
-            }
+            display.draw_bitmap_colored(0, 216, bmp, Pixel::Black, ctx);
         }
     }
 }
\ No newline at end of file
@@ -2,8 +2,9 @@ use crate::pages::{Page, Action};
 use crate::context::Context;
 use crate::display::SharpDisplay;
 use crate::ui::fonts::FontRenderer;
+use crate::ui::layout::{Align, TextLayout};
+use crate::paths::paths;
 use termion::event::Key;
-use rpi_memory_display::Pixel;
 use std::path::PathBuf;
 use std::fs::File;
 use std::io::Read;
@@ -35,8 +36,8 @@ pub struct LearnPage {
 
 impl LearnPage {
     pub fn new(path: PathBuf) -> Self {
-        let renderer = FontRenderer::new("/home/kramwriter/KramWriter/fonts/Inter_28pt-Medium.ttf");
-        let ui_renderer = FontRenderer::new("/home/kramwriter/KramWriter/fonts/BebasNeue-Regular.ttf");
+        let renderer = FontRenderer::chain(&paths().text_fonts());
+        let ui_renderer = FontRenderer::new(&paths().font("BebasNeue-Regular.ttf"));
 
         let mut page = Self {
             path,
@@ -142,41 +143,11 @@ impl LearnPage {
     fn draw_centered_wrapped_text(&self, display: &mut SharpDisplay, text: &str, ctx: &Context) {
         let font_size = 24.0;
         let margin = 20;
-        let max_width = 400 - (margin * 2);
-        let mut lines = Vec::new();
-
-        // Process text paragraph by paragraph to respect explicit newlines
-        for paragraph in text.split('\n') {
-            let mut current_line = String::new();
-            for word in paragraph.split_whitespace() {
-                let test_str = if current_line.is_empty() { 
-                    word.to_string() 
-                } else { 
-                    format!("{} {}", current_line, word) 
-                };
-
-                if self.renderer.calculate_width(&test_str, font_size) as i32 > max_width {
-                    lines.push(current_line);
-                    current_line = word.to_string();
-                } else {
-                    current_line = test_str;
-                }
-            }
-            if !current_line.is_empty() {
-                lines.push(current_line);
-            }
-        }
-
-        let line_height = 30;
-        let total_height = lines.len() as i32 * line_height;
-        let mut start_y = (240 - total_height) / 2;
+        let max_width = display.width() as i32 - (margin * 2);
+        let layout = TextLayout::new(text, &self.renderer, font_size, max_width, Align::Center).with_line_height(30);
 
-        for line in lines {
-            let w = self.renderer.calculate_width(&line, font_size);
-            let x = 200 - (w / 2);
-            self.renderer.draw_text_colored(display, &line, x, start_y + 24, font_size, Pixel::Black, ctx);
-            start_y += line_height;
-        }
+        let start_y = (display.height() as i32 - layout.height()) / 2;
+        layout.draw(text, display, margin, start_y, ctx);
     }
 }
 
@@ -210,7 +181,7 @@ impl Page for LearnPage {
         if self.deck.is_empty() {
             let msg = "NO CARDS FOUND";
             let w = self.ui_renderer.calculate_width(msg, 30.0);
-            self.ui_renderer.draw_text(display, msg, 200 - (w/2), 120, 30.0, ctx);
+            self.ui_renderer.draw_text(display, msg, display.width() as i32 / 2 - (w/2), 120, 30.0, ctx);
             return;
         }
 
@@ -233,6 +204,6 @@ impl Page for LearnPage {
             "SPACE: TOGGLE | ENTER: NEXT"
         };
         let f_width = self.ui_renderer.calculate_width(footer, 18.0);
-        self.ui_renderer.draw_text(display, footer, 200 - (f_width / 2), 230, 18.0, ctx);
+        self.ui_renderer.draw_text(display, footer, display.width() as i32 / 2 - (f_width / 2), 230, 18.0, ctx);
     }
 }
\ No newline at end of file
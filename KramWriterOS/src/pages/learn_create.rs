@@ -2,6 +2,8 @@ use crate::pages::{Page, Action};
 use crate::context::Context;
 use crate::display::SharpDisplay;
 use crate::ui::fonts::FontRenderer;
+use crate::ui::layout::{Align, TextLayout};
+use crate::paths::paths;
 use termion::event::Key;
 use rpi_memory_display::Pixel;
 use std::path::PathBuf;
@@ -12,6 +14,15 @@ use std::io::{Read, Write}; // Added Read for DB packaging
 use rusqlite::Connection;
 use zip::write::FileOptions;
 
+/// Where the card text is drawn: its left edge, the top of its first
+/// line, its width and the bottom above the footer. Longer text
+/// scrolls.
+const TEXT_LEFT: i32 = 20;
+const TEXT_TOP: i32 = 96;
+const TEXT_WIDTH: i32 = 360;
+const TEXT_BOTTOM: i32 = 212;
+const FONT_SIZE: f32 = 24.0;
+
 #[derive(PartialEq)]
 enum EditSide {
     Front,
@@ -30,6 +41,8 @@ pub struct LearnCreatePage {
     cards: Vec<CardEditor>,
     current_index: usize,
     side: EditSide,
+    /// The first line of the side being edited that is shown
+    scroll_line_offset: usize,
     renderer: FontRenderer,
     ui_renderer: FontRenderer,
     message: Option<(String, std::time::Instant)>,
@@ -37,8 +50,8 @@ pub struct LearnCreatePage {
 
 impl LearnCreatePage {
     pub fn new(path: PathBuf) -> Self {
-        let renderer = FontRenderer::new("/home/kramwriter/KramWriter/fonts/Inter_28pt-Medium.ttf");
-        let ui_renderer = FontRenderer::new("/home/kramwriter/KramWriter/fonts/BebasNeue-Regular.ttf");
+        let renderer = FontRenderer::chain(&paths().text_fonts());
+        let ui_renderer = FontRenderer::new(&paths().font("BebasNeue-Regular.ttf"));
 
         let first_card = CardEditor {
             front: String::new(),
@@ -52,6 +65,7 @@ impl LearnCreatePage {
             cards: vec![first_card],
             current_index: 0,
             side: EditSide::Front,
+            scroll_line_offset: 0,
             renderer,
             ui_renderer,
             message: None,
@@ -62,6 +76,46 @@ impl LearnCreatePage {
         &mut self.cards[self.current_index]
     }
 
+    /// The side being edited and the cursor in it, a byte offset.
+    fn current_side(&self) -> (&str, usize) {
+        let card = &self.cards[self.current_index];
+        match self.side {
+            EditSide::Front => (&card.front, card.front_cursor),
+            EditSide::Back => (&card.back, card.back_cursor),
+        }
+    }
+
+    fn current_side_mut(&mut self) -> (&mut String, &mut usize) {
+        let is_front = self.side == EditSide::Front;
+        let card = &mut self.cards[self.current_index];
+        if is_front {
+            (&mut card.front, &mut card.front_cursor)
+        } else {
+            (&mut card.back, &mut card.back_cursor)
+        }
+    }
+
+    fn layout(&self, text: &str) -> TextLayout {
+        TextLayout::new(text, &self.renderer, FONT_SIZE, TEXT_WIDTH, Align::Left)
+    }
+
+    fn visible_lines(layout: &TextLayout) -> usize {
+        ((TEXT_BOTTOM - TEXT_TOP) / layout.line_height()) as usize
+    }
+
+    /// Scrolls the side being edited so the cursor's line is shown.
+    fn scroll_to_cursor(&mut self) {
+        let (text, cursor) = self.current_side();
+        let layout = self.layout(text);
+        let visible_lines = Self::visible_lines(&layout);
+        let line = layout.line_of(cursor);
+        if line < self.scroll_line_offset {
+            self.scroll_line_offset = line;
+        } else if line >= self.scroll_line_offset + visible_lines {
+            self.scroll_line_offset = line + 1 - visible_lines;
+        }
+    }
+
     fn save_to_file(&mut self) {
         let temp_db_path = "/tmp/create_anki.db";
         
@@ -149,14 +203,18 @@ impl LearnCreatePage {
     }
 
     fn draw_editor_text(&self, display: &mut SharpDisplay, text: &str, cursor_pos: usize, ctx: &Context) {
-        let font_size = 24.0;
-        let x = 20;
-        let y = 120;
-        self.renderer.draw_text_colored(display, text, x, y, font_size, Pixel::Black, ctx);
-
-        let cursor_x = x + self.renderer.calculate_width(&text[0..cursor_pos.min(text.len())], font_size) as i32;
-        for cy in (y - 22)..(y + 4) {
-            if cy >= 0 && cy < 240 && cursor_x >= 0 && cursor_x < 400 {
+        let layout = self.layout(text);
+        let shown = self.scroll_line_offset..self.scroll_line_offset + Self::visible_lines(&layout);
+        layout.draw_lines(text, display, shown.clone(), TEXT_LEFT, TEXT_TOP, ctx);
+        if !shown.contains(&layout.line_of(cursor_pos)) {
+            return;
+        }
+
+        let (x, y) = layout.position(text, cursor_pos);
+        let scrolled = self.scroll_line_offset as i32 * layout.line_height();
+        let (cursor_x, cursor_y) = (TEXT_LEFT + x, TEXT_TOP + y - scrolled);
+        for cy in (cursor_y + 2)..(cursor_y + 28) {
+            if cy >= 0 && cy < display.height() as i32 && cursor_x >= 0 && cursor_x < display.width() as i32 {
                 display.draw_pixel(cursor_x as usize, cy as usize, Pixel::Black, ctx);
                 display.draw_pixel((cursor_x + 1) as usize, cy as usize, Pixel::Black, ctx);
             }
@@ -172,7 +230,7 @@ impl Page for LearnCreatePage {
             }
         }
 
-        match key {
+        let action = match key {
             Key::Ctrl('s') => {
                 self.save_to_file();
                 Action::None
@@ -200,59 +258,55 @@ impl Page for LearnCreatePage {
                 Action::None
             }
             Key::Left => {
-                let is_front = self.side == EditSide::Front;
-                let card = self.current_card_mut();
-                if is_front && card.front_cursor > 0 {
-                    card.front_cursor -= 1;
-                } else if !is_front && card.back_cursor > 0 {
-                    card.back_cursor -= 1;
+                let (text, cursor) = self.current_side_mut();
+                if let Some((idx, _)) = text[..*cursor].char_indices().next_back() {
+                    *cursor = idx;
                 }
                 Action::None
             }
             Key::Right => {
-                let is_front = self.side == EditSide::Front;
-                let card = self.current_card_mut();
-                if is_front && card.front_cursor < card.front.len() {
-                    card.front_cursor += 1;
-                } else if !is_front && card.back_cursor < card.back.len() {
-                    card.back_cursor += 1;
+                let (text, cursor) = self.current_side_mut();
+                if let Some(c) = text[*cursor..].chars().next() {
+                    *cursor += c.len_utf8();
+                }
+                Action::None
+            }
+            Key::Up | Key::Down => {
+                let (text, cursor) = self.current_side();
+                let layout = self.layout(text);
+                let (x, y) = layout.position(text, cursor);
+                let y = if key == Key::Up { y - layout.line_height() } else { y + layout.line_height() };
+                if y >= 0 && y < layout.height() {
+                    let target = layout.cursor_at(text, x, y);
+                    *self.current_side_mut().1 = target;
                 }
                 Action::None
             }
             Key::Char(c) => {
                 if c.is_control() { return Action::None; }
-                let is_front = self.side == EditSide::Front;
-                let card = self.current_card_mut();
-                if is_front {
-                    card.front.insert(card.front_cursor, c);
-                    card.front_cursor += 1;
-                } else {
-                    card.back.insert(card.back_cursor, c);
-                    card.back_cursor += 1;
-                }
+                let (text, cursor) = self.current_side_mut();
+                text.insert(*cursor, c);
+                *cursor += c.len_utf8();
                 Action::None
             }
             Key::Backspace => {
-                let is_front = self.side == EditSide::Front;
-                let card = self.current_card_mut();
-                if is_front && card.front_cursor > 0 {
-                    card.front.remove(card.front_cursor - 1);
-                    card.front_cursor -= 1;
-                } else if !is_front && card.back_cursor > 0 {
-                    card.back.remove(card.back_cursor - 1);
-                    card.back_cursor -= 1;
+                let (text, cursor) = self.current_side_mut();
+                if let Some((idx, _)) = text[..*cursor].char_indices().next_back() {
+                    text.remove(idx);
+                    *cursor = idx;
                 }
                 Action::None
             }
             Key::Esc => Action::Pop, 
             _ => Action::None,
-        }
+        };
+        self.scroll_to_cursor();
+        action
     }
 
     fn draw(&self, display: &mut SharpDisplay, ctx: &Context) {
         display.clear(ctx);
 
-        let card = &self.cards[self.current_index];
         let side_text = if self.side == EditSide::Front { "EDIT FRONT" } else { "EDIT BACK" };
         self.ui_renderer.draw_text(display, side_text, 10, 25, 20.0, ctx);
 
@@ -262,17 +316,14 @@ impl Page for LearnCreatePage {
 
         if let Some((msg, _)) = &self.message {
             let m_w = self.ui_renderer.calculate_width(msg, 20.0);
-            self.ui_renderer.draw_text(display, msg, 200 - (m_w / 2), 50, 20.0, ctx);
+            self.ui_renderer.draw_text(display, msg, display.width() as i32 / 2 - (m_w / 2), 50, 20.0, ctx);
         }
 
-        if self.side == EditSide::Front {
-            self.draw_editor_text(display, &card.front, card.front_cursor, ctx);
-        } else {
-            self.draw_editor_text(display, &card.back, card.back_cursor, ctx);
-        }
+        let (text, cursor) = self.current_side();
+        self.draw_editor_text(display, text, cursor, ctx);
 
         let footer = "CTRL+T: FLIP | CTRL+P/N: NAV | CTRL+S: SAVE | CTRL+D: DEL";
         let f_w = self.ui_renderer.calculate_width(footer, 15.0);
-        self.ui_renderer.draw_text(display, footer, 200 - (f_w / 2), 230, 15.0, ctx);
+        self.ui_renderer.draw_text(display, footer, display.width() as i32 / 2 - (f_w / 2), 230, 15.0, ctx);
     }
 }
\ No newline at end of file
@@ -7,6 +7,7 @@ use rpi_memory_display::Pixel;
 
 // --- Added the import for the Learn file browser ---
 use crate::pages::file_browser_learn::{FileBrowserLearnPage, BrowserMode};
+use crate::paths::paths;
 
 pub struct LearnMenuPage {
     current_index: usize, 
@@ -16,7 +17,7 @@ pub struct LearnMenuPage {
 
 impl LearnMenuPage {
     pub fn new() -> Self {
-        let asset_path = "/home/kramwriter/KramWriter/assets/Learn/Menu";
+        let asset_path = paths().asset("Learn/Menu");
         
         Self {
             current_index: 0,
@@ -29,14 +30,7 @@ impl LearnMenuPage {
     }
 
     fn draw_full_screen(&self, display: &mut SharpDisplay, bmp: &Bitmap, ctx: &Context) {
-        for y in 0..bmp.height.min(240) {
-            for x in 0..bmp.width.min(400) {
-                let pixel = bmp.pixels[y * bmp.width + x];
-                if pixel == Pixel::Black {
-                    display.draw_pixel(x, y, Pixel::Black, ctx);
-                }
-            }
-        }
+        display.draw_bitmap_colored(0, 0, bmp, Pixel::Black, ctx);
     }
 }
 
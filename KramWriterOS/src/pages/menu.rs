@@ -2,6 +2,7 @@ use crate::pages::{Page, Action};
 use crate::context::Context;
 use crate::display::SharpDisplay;
 use crate::ui::bitmap::Bitmap;
+use crate::paths::paths;
 
 use termion::event::Key;
 
@@ -23,7 +24,7 @@ impl MenuPage {
         for option in MENU_OPTIONS.iter() {
             let mut variants = Vec::new();
             for suffix in 0..3 {
-                let path = format!("/home/kramwriter/KramWriter/assets/Menu/{}_{}.bmp", option, suffix);
+                let path = paths().asset(&format!("Menu/{}_{}.bmp", option, suffix));
                 variants.push(Bitmap::load(&path).ok());
             }//test
             images.push(variants);
@@ -32,16 +33,8 @@ impl MenuPage {
     }
 
     fn draw_bitmap_at(&self, display: &mut SharpDisplay, bmp: &Bitmap, y_pos: i32, ctx: &Context) {
-        let start_x = (400usize.saturating_sub(bmp.width)) / 2;
-        for y in 0..bmp.height {
-            let screen_y = y_pos + y as i32;
-            if screen_y >= 0 && screen_y < 240 {
-                for x in 0..bmp.width {
-                    let pixel = bmp.pixels[y * bmp.width + x];
-                    display.draw_pixel(start_x + x, screen_y as usize, pixel, ctx);
-                }
-            }
-        }
+        let start_x = (display.width().saturating_sub(bmp.width)) / 2;
+        display.draw_bitmap(start_x as i32, y_pos, bmp, ctx);
     }
 }
 
@@ -80,7 +73,7 @@ impl Page for MenuPage {
     fn draw(&self, display: &mut SharpDisplay, ctx: &Context) {
         // CENTER: Suffix _0 (Variant index 0)
         if let Some(main_bmp) = self.images[self.current_index][0].as_ref() {
-            let cy = (240i32 - main_bmp.height as i32) / 2;
+            let cy = (display.height() as i32 - main_bmp.height as i32) / 2;
             self.draw_bitmap_at(display, main_bmp, cy, ctx);
             let h = main_bmp.height as i32;
 
@@ -3,6 +3,7 @@ use crate::context::Context;
 use crate::display::SharpDisplay;
 use crate::ui::bitmap::Bitmap;
 use crate::ui::fonts::FontRenderer;
+use crate::paths::paths;
 use termion::event::Key;
 use rpi_memory_display::Pixel;
 use std::fs;
@@ -30,8 +31,8 @@ pub struct NameEntryPage {
 
 impl NameEntryPage {
     pub fn new(parent_path: PathBuf, is_folder: bool) -> Self {
-        let renderer = FontRenderer::new("/home/kramwriter/KramWriter/fonts/BebasNeue-Regular.ttf");
-        let asset_path = "/home/kramwriter/KramWriter/assets/NameEntry";
+        let renderer = FontRenderer::new(&paths().font("BebasNeue-Regular.ttf"));
+        let asset_path = paths().asset("NameEntry");
         
         Self {
             title_folder_bmp: Bitmap::load(&format!("{}/title.bmp", asset_path)).ok(),
@@ -133,14 +134,8 @@ impl Page for NameEntryPage {
         // 1. Draw correct Title
         let title = if self.is_folder { &self.title_folder_bmp } else { &self.title_file_bmp };
         if let Some(bmp) = title {
-            let x_off = (400 - bmp.width as i32) / 2;
-            for y in 0..bmp.height {
-                for x in 0..bmp.width {
-                    if bmp.pixels[y * bmp.width + x] == Pixel::Black {
-                        display.draw_pixel((x as i32 + x_off) as usize, (y + 40) as usize, Pixel::Black, ctx);
-                    }
-                }
-            }
+            let x_off = (display.width() as i32 - bmp.width as i32) / 2;
+            display.draw_bitmap_colored(x_off, 40, bmp, Pixel::Black, ctx);
         }
 
         // 2. Prepare Display String
@@ -152,7 +147,7 @@ impl Page for NameEntryPage {
         };
 
         let full_width = self.renderer.calculate_width(&display_text, font_size);
-        let start_x = 200 - (full_width / 2);
+        let start_x = display.width() as i32 / 2 - (full_width / 2);
         let text_y = 120;
         
         self.renderer.draw_text(display, &display_text, start_x, text_y, font_size, ctx);
@@ -164,7 +159,7 @@ impl Page for NameEntryPage {
             let cursor_x = start_x + sub_width; 
 
             for cy in (text_y - 28)..(text_y + 2) {
-                if cy > 0 && cy < 240 {
+                if cy > 0 && cy < display.height() as i32 {
                     display.draw_pixel(cursor_x as usize, cy as usize, Pixel::Black, ctx);
                     display.draw_pixel((cursor_x + 1) as usize, cy as usize, Pixel::Black, ctx);
                 }
@@ -174,19 +169,13 @@ impl Page for NameEntryPage {
         // 4. Error Message
         if let Some(err) = &self.error_msg {
             let err_w = self.renderer.calculate_width(err, 20.0);
-            self.renderer.draw_text(display, err, 200 - (err_w / 2), 165, 20.0, ctx);
+            self.renderer.draw_text(display, err, display.width() as i32 / 2 - (err_w / 2), 165, 20.0, ctx);
         }
 
         // 5. Footer
         let footer_idx = if self.focus == EntryFocus::TextInput { 0 } else { self.footer_index + 1 };
         if let Some(bmp) = &self.footer_variants[footer_idx] {
-            for y in 0..bmp.height {
-                for x in 0..bmp.width {
-                    if bmp.pixels[y * bmp.width + x] == Pixel::Black {
-                        display.draw_pixel(x, (y + 216) as usize, Pixel::Black, ctx);
-                    }
-                }
-            }
+            display.draw_bitmap_colored(0, 216, bmp, Pixel::Black, ctx);
         }
     }
 }
\ No newline at end of file
@@ -8,6 +8,7 @@ use rpi_memory_display::Pixel;
 use std::fs;
 use std::path::PathBuf;
 use crate::pages::learn_create::LearnCreatePage;
+use crate::paths::paths;
 
 #[derive(PartialEq)]
 enum EntryFocus {
@@ -31,9 +32,9 @@ pub struct NameEntryLearnPage {
 
 impl NameEntryLearnPage {
     pub fn new(parent_path: PathBuf, is_folder: bool) -> Self {
-        let renderer = FontRenderer::new("/home/kramwriter/KramWriter/fonts/BebasNeue-Regular.ttf");
+        let renderer = FontRenderer::new(&paths().font("BebasNeue-Regular.ttf"));
         // You might want to duplicate the NameEntry assets to a Learn folder eventually
-        let asset_path = "/home/kramwriter/KramWriter/assets/NameEntry"; 
+        let asset_path = paths().asset("NameEntry"); 
         
         Self {
             title_folder_bmp: Bitmap::load(&format!("{}/title.bmp", asset_path)).ok(),
@@ -132,14 +133,8 @@ impl Page for NameEntryLearnPage {
     fn draw(&self, display: &mut SharpDisplay, ctx: &Context) {
         let title = if self.is_folder { &self.title_folder_bmp } else { &self.title_file_bmp };
         if let Some(bmp) = title {
-            let x_off = (400 - bmp.width as i32) / 2;
-            for y in 0..bmp.height {
-                for x in 0..bmp.width {
-                    if bmp.pixels[y * bmp.width + x] == Pixel::Black {
-                        display.draw_pixel((x as i32 + x_off) as usize, (y + 40) as usize, Pixel::Black, ctx);
-                    }
-                }
-            }
+            let x_off = (display.width() as i32 - bmp.width as i32) / 2;
+            display.draw_bitmap_colored(x_off, 40, bmp, Pixel::Black, ctx);
         }
 
         let font_size = 32.0;
@@ -152,7 +147,7 @@ impl Page for NameEntryLearnPage {
         };
 
         let full_width = self.renderer.calculate_width(&display_text, font_size);
-        let start_x = 200 - (full_width / 2);
+        let start_x = display.width() as i32 / 2 - (full_width / 2);
         let text_y = 120;
         
         self.renderer.draw_text(display, &display_text, start_x, text_y, font_size, ctx);
@@ -163,7 +158,7 @@ impl Page for NameEntryLearnPage {
             let cursor_x = start_x + sub_width; 
 
             for cy in (text_y - 28)..(text_y + 2) {
-                if cy > 0 && cy < 240 {
+                if cy > 0 && cy < display.height() as i32 {
                     display.draw_pixel(cursor_x as usize, cy as usize, Pixel::Black, ctx);
                     display.draw_pixel((cursor_x + 1) as usize, cy as usize, Pixel::Black, ctx);
                 }
@@ -172,18 +167,12 @@ impl Page for NameEntryLearnPage {
 
         if let Some(err) = &self.error_msg {
             let err_w = self.renderer.calculate_width(err, 20.0);
-            self.renderer.draw_text(display, err, 200 - (err_w / 2), 165, 20.0, ctx);
+            self.renderer.draw_text(display, err, display.width() as i32 / 2 - (err_w / 2), 165, 20.0, ctx);
         }
 
         let footer_idx = if self.focus == EntryFocus::TextInput { 0 } else { self.footer_index + 1 };
         if let Some(bmp) = &self.footer_variants[footer_idx] {
-            for y in 0..bmp.height {
-                for x in 0..bmp.width {
-                    if bmp.pixels[y * bmp.width + x] == Pixel::Black {
-                        display.draw_pixel(x, (y + 216) as usize, Pixel::Black, ctx);
-                    }
-                }
-            }
+            display.draw_bitmap_colored(0, 216, bmp, Pixel::Black, ctx);
         }
     }
 }
\ No newline at end of file
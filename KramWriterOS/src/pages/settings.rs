@@ -1,18 +1,28 @@
 use crate::pages::{Page, Action};
 use crate::context::{Context, KeyboardLayout};
 use crate::display::SharpDisplay;
+use crate::keymap;
 use crate::ui::bitmap::Bitmap;
+use crate::ui::fonts::FontRenderer;
 use termion::event::Key;
 use rpi_memory_display::Pixel;
 use crate::pages::simplenote_setup::SimpleNoteSetupPage;
 use crate::pages::timezone::TimezonePage;
+use crate::paths::paths;
 
 // Swapped "drive" for "simplenote"
 const SETTINGS_OPTIONS: [&str; 5] = ["timezone", "location", "darkmode", "simplenote", "keyboard"];
 
+/// The keyboard row of layouts without art (all but QWERTY and
+/// QWERTZ) is text. Size and position match the art.
+const KEYBOARD_SIZE: f32 = 32.8;
+const KEYBOARD_BASELINE: i32 = 208;
+const KEYBOARD_BOX: (usize, usize) = (184, 28);
+
 pub struct SettingsPage {
     current_index: usize,
     images: Vec<[Option<Bitmap>; 4]>,
+    renderer: FontRenderer,
 }
 
 impl SettingsPage {
@@ -22,21 +32,13 @@ impl SettingsPage {
         for option in SETTINGS_OPTIONS.iter() {
             let mut variants = [None, None, None, None];
 
-            if *option == "darkmode" {
-                let paths = [
-                    "/home/kramwriter/KramWriter/assets/Settings/darkmode_0.bmp",
-                    "/home/kramwriter/KramWriter/assets/Settings/darkmode_1.bmp",
-                    "/home/kramwriter/KramWriter/assets/Settings/darkmode_2.bmp",
-                    "/home/kramwriter/KramWriter/assets/Settings/darkmode_3.bmp",
-                ];
-                for (i, path) in paths.iter().enumerate() {
-                    variants[i] = Bitmap::load(path).ok();
+            if *option == "darkmode" || *option == "keyboard" {
+                for (i, variant) in variants.iter_mut().enumerate() {
+                    *variant = Bitmap::load(&paths().asset(&format!("Settings/{}_{}.bmp", option, i))).ok();
                 }
             } else {
-                // Suffix logic: keyboard uses _3 for selected, others use _1
-                let suffix_sel = if *option == "keyboard" { "3" } else { "1" };
-                let path_0 = format!("/home/kramwriter/KramWriter/assets/Settings/{}_0.bmp", option);
-                let path_sel = format!("/home/kramwriter/KramWriter/assets/Settings/{}_{}.bmp", option, suffix_sel);
+                let path_0 = paths().asset(&format!("Settings/{}_0.bmp", option));
+                let path_sel = paths().asset(&format!("Settings/{}_1.bmp", option));
                 
                 variants[0] = Bitmap::load(&path_0).ok();
                 variants[1] = Bitmap::load(&path_sel).ok();
@@ -45,7 +47,35 @@ impl SettingsPage {
             images.push(variants);
         }
 
-        Self { current_index: 0, images }
+        let renderer = FontRenderer::new(&paths().font("BebasNeue-Regular.ttf"));
+        Self { current_index: 0, images, renderer }
+    }
+
+    fn draw_keyboard(&self, display: &mut SharpDisplay, variants: &[Option<Bitmap>; 4], selected: bool, ctx: &Context) {
+        // Art: QWERTY=0, QWERTZ=1, selected QWERTZ=2, selected QWERTY=3
+        let art = match (&ctx.layout, selected) {
+            (KeyboardLayout::Qwerty, false) => &variants[0],
+            (KeyboardLayout::Qwertz, false) => &variants[1],
+            (KeyboardLayout::Qwertz, true) => &variants[2],
+            (KeyboardLayout::Qwerty, true) => &variants[3],
+            _ => &None,
+        };
+        if let Some(bmp) = art {
+            display.draw_bitmap_colored(0, 0, bmp, Pixel::Black, ctx);
+            return;
+        }
+
+        let label = format!("KEYBOARD LAYOUT: {}", ctx.layout.name().to_uppercase());
+        let width = self.renderer.calculate_width(&label, KEYBOARD_SIZE);
+        let x = (display.width() as i32 - width) / 2;
+        let color = if selected {
+            let (y, height) = KEYBOARD_BOX;
+            display.fill_rect((x - 2).max(0) as usize, y, width as usize + 4, height, Pixel::Black, ctx);
+            Pixel::White
+        } else {
+            Pixel::Black
+        };
+        self.renderer.draw_text_colored(display, &label, x, KEYBOARD_BASELINE, KEYBOARD_SIZE, color, ctx);
     }
 }
 
@@ -62,7 +92,7 @@ impl Page for SettingsPage {
             }
             Key::Char('\n') => {
                 match self.current_index {
-                    0 => Action::Push(Box::new(TimezonePage::new())),
+                    0 => Action::Push(Box::new(TimezonePage::new(ctx.tz()))),
                     2 => {
                         ctx.dark_mode = !ctx.dark_mode;
                         Action::None
@@ -72,10 +102,11 @@ impl Page for SettingsPage {
                         Action::Push(Box::new(SimpleNoteSetupPage::new()))
                     }
                     4 => {
-                        ctx.layout = match ctx.layout {
-                            KeyboardLayout::Qwerty => KeyboardLayout::Qwertz,
-                            KeyboardLayout::Qwertz => KeyboardLayout::Qwerty,
-                        };
+                        // Cycles through the built-in and keymap file layouts
+                        let layouts = keymap::layouts();
+                        let current = layouts.iter().position(|layout| *layout == ctx.layout);
+                        let next = current.map_or(0, |i| (i + 1) % layouts.len());
+                        ctx.layout = layouts[next].clone();
                         Action::None
                     }
                     _ => Action::None,
@@ -88,6 +119,11 @@ impl Page for SettingsPage {
 
     fn draw(&self, display: &mut SharpDisplay, ctx: &Context) {
         for (i, variants) in self.images.iter().enumerate() {
+            if SETTINGS_OPTIONS[i] == "keyboard" {
+                self.draw_keyboard(display, variants, i == self.current_index, ctx);
+                continue;
+            }
+
             let variant_idx;
 
             if SETTINGS_OPTIONS[i] == "darkmode" {
@@ -103,14 +139,7 @@ impl Page for SettingsPage {
             }
 
             if let Some(bmp) = &variants[variant_idx] {
-                for y in 0..bmp.height.min(240) {
-                    for x in 0..bmp.width.min(400) {
-                        let pixel = bmp.pixels[y * bmp.width + x];
-                        if pixel == Pixel::Black {
-                            display.draw_pixel(x, y, pixel, ctx);
-                        }
-                    }
-                }
+                display.draw_bitmap_colored(0, 0, bmp, Pixel::Black, ctx);
             }
         }
     }
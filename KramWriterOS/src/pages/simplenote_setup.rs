@@ -3,10 +3,10 @@ use crate::context::Context;
 use crate::display::SharpDisplay;
 use crate::ui::bitmap::Bitmap;
 use crate::ui::fonts::FontRenderer;
+use crate::paths::paths;
 use termion::event::Key;
 use rpi_memory_display::Pixel;
 use std::fs;
-use std::path::Path;
 use std::process::{Command, Stdio};
 use std::io::{BufRead, BufReader};
 use std::sync::mpsc;
@@ -42,11 +42,10 @@ pub struct SimpleNoteSetupPage {
 
 impl SimpleNoteSetupPage {
     pub fn new() -> Self {
-        let renderer = FontRenderer::new("/home/kramwriter/KramWriter/fonts/BebasNeue-Regular.ttf");
-        let asset_path = "/home/kramwriter/KramWriter/assets/NameEntry"; 
+        let renderer = FontRenderer::new(&paths().font("BebasNeue-Regular.ttf"));
+        let asset_path = paths().asset("NameEntry"); 
         
-        let creds_path = "/home/kramwriter/.simplenote_creds";
-        let initial_step = if Path::new(creds_path).exists() {
+        let initial_step = if paths().simplenote_creds().exists() {
             SetupStep::ReadyToSync
         } else {
             SetupStep::Email
@@ -90,7 +89,10 @@ impl SimpleNoteSetupPage {
                 }
                 
                 let creds = format!("{}\n{}", self.email, self.password);
-                if fs::write("/home/kramwriter/.simplenote_creds", creds).is_ok() {
+                let creds_path = paths().simplenote_creds();
+                let saved = creds_path.parent().map_or(Ok(()), fs::create_dir_all)
+                    .and_then(|_| fs::write(&creds_path, creds));
+                if saved.is_ok() {
                     self.step = SetupStep::ReadyToSync;
                     self.focus = EntryFocus::BottomBar;
                     self.footer_index = 1;
@@ -112,7 +114,9 @@ impl SimpleNoteSetupPage {
                 thread::spawn(move || {
                     let child = Command::new("python3")
                         .arg("-u") 
-                        .arg("/home/kramwriter/KramWriter/KramWriterOS/scripts/sync_notes.py")
+                        .arg(paths().scripts.join("sync_notes.py"))
+                        .env("KRAMWRITER_SIMPLENOTE_CREDS", paths().simplenote_creds())
+                        .env("KRAMWRITER_SIMPLENOTE_DIR", paths().simplenote_notes())
                         .stdout(Stdio::piped())
                         .stderr(Stdio::piped())
                         .spawn();
@@ -260,7 +264,7 @@ impl Page for SimpleNoteSetupPage {
             SetupStep::Syncing => "SYNCING NOTES...",
         };
         let title_w = self.renderer.calculate_width(title_text, 24.0);
-        self.renderer.draw_text(display, title_text, 200 - (title_w / 2), 60, 24.0, ctx);
+        self.renderer.draw_text(display, title_text, display.width() as i32 / 2 - (title_w / 2), 60, 24.0, ctx);
 
         let font_size = 28.0;
         let display_text = match self.step {
@@ -274,7 +278,7 @@ impl Page for SimpleNoteSetupPage {
         };
 
         let full_width = self.renderer.calculate_width(&display_text, font_size);
-        let start_x = 200 - (full_width / 2);
+        let start_x = display.width() as i32 / 2 - (full_width / 2);
         let text_y = 120;
         self.renderer.draw_text(display, &display_text, start_x, text_y, font_size, ctx);
 
@@ -291,22 +295,16 @@ impl Page for SimpleNoteSetupPage {
 
         if let Some(err) = &self.error_msg {
             let err_w = self.renderer.calculate_width(err, 20.0);
-            self.renderer.draw_text(display, err, 200 - (err_w / 2), 165, 20.0, ctx);
+            self.renderer.draw_text(display, err, display.width() as i32 / 2 - (err_w / 2), 165, 20.0, ctx);
         } else if let Some(status) = &self.status_msg {
             let status_w = self.renderer.calculate_width(status, 20.0);
-            self.renderer.draw_text(display, status, 200 - (status_w / 2), 165, 20.0, ctx);
+            self.renderer.draw_text(display, status, display.width() as i32 / 2 - (status_w / 2), 165, 20.0, ctx);
         }
 
         if self.step != SetupStep::Syncing {
             let footer_idx = if self.focus == EntryFocus::TextInput { 0 } else { self.footer_index + 1 };
             if let Some(bmp) = &self.footer_variants[footer_idx] {
-                for y in 0..bmp.height {
-                    for x in 0..bmp.width {
-                        if bmp.pixels[y * bmp.width + x] == Pixel::Black {
-                            display.draw_pixel(x, (y + 216) as usize, Pixel::Black, ctx);
-                        }
-                    }
-                }
+                display.draw_bitmap_colored(0, 216, bmp, Pixel::Black, ctx);
             }
         }
     }
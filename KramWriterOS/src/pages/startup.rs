@@ -2,6 +2,7 @@ use crate::pages::{Page, Action, menu::MenuPage};
 use crate::context::Context;
 use crate::display::SharpDisplay;
 use crate::ui::bitmap::Bitmap;
+use crate::paths::paths;
 use termion::event::Key;
 
 pub struct LogoPage {
@@ -10,10 +11,10 @@ pub struct LogoPage {
 
 impl LogoPage {
     pub fn new() -> Self {
-        let path = "/home/kramwriter/KramWriter/assets/logo/logo.bmp";
+        let path = paths().asset("logo/logo.bmp");
         println!("Loading logo from: {}", path);
         
-        let logo = Bitmap::load(path).ok();
+        let logo = Bitmap::load(&path).ok();
         
         Self { logo }
     }
@@ -34,21 +35,9 @@ impl Page for LogoPage {
 
         if let Some(bmp) = &self.logo {
             // Center the logo
-            let start_x = (400usize.saturating_sub(bmp.width)) / 2;
-            let start_y = (240usize.saturating_sub(bmp.height)) / 2;
-            
-            for y in 0..bmp.height {
-                let screen_y = start_y + y;
-                if screen_y >= 240 { continue; } // Safety check
-
-                for x in 0..bmp.width {
-                    let screen_x = start_x + x;
-                    if screen_x >= 400 { continue; } // Safety check
-
-                    let pixel = bmp.pixels[y * bmp.width + x];
-                    display.draw_pixel(screen_x, screen_y, pixel, ctx);
-                }
-            }
+            let start_x = (display.width().saturating_sub(bmp.width)) / 2;
+            let start_y = (display.height().saturating_sub(bmp.height)) / 2;
+            display.draw_bitmap(start_x as i32, start_y as i32, bmp, ctx);
         } else {
             // Fallback if file missing
             display.draw_text(150, 100, "LOGO MISSING", ctx);
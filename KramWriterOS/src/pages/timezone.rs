@@ -2,41 +2,115 @@ use crate::pages::{Page, Action};
 use crate::context::Context;
 use crate::display::SharpDisplay;
 use crate::ui::bitmap::Bitmap;
+use crate::ui::fonts::FontRenderer;
+use crate::paths::paths;
+use chrono::{Offset, TimeZone, Utc};
+use chrono_tz::{Tz, TZ_VARIANTS};
 use termion::event::Key;
 use rpi_memory_display::Pixel;
 
 // Matches your new simplified filenames: "3.bmp", "-3.bmp", "UTC.bmp", etc.
 const OFFSETS: [&str; 31] = [
-    "-11", "-10", "-9", "-8", "-7", "-6", "-5", "-4", "-3.5", "-3", "-2", "-1", 
+    "-11", "-10", "-9", "-8", "-7", "-6", "-5", "-4", "-3.5", "-3", "-2", "-1",
     "UTC", "1", "2", "3", "3.5", "4", "4.5", "5", "5.5", "6", "6.5", "7", "8", "9", "9.5", "10", "10.5", "11", "12"
 ];
 
+/// The search box covers the bottom of the map (mostly ocean and
+/// Antarctica): the query and this many matching zones.
+const BOX_TOP: usize = 150;
+const ROW_HEIGHT: usize = 22;
+const VISIBLE_MATCHES: usize = 3;
+
+/// Picks a named timezone: typing filters the list, Up/Down selects,
+/// Enter saves. The map highlights the selected zone's current offset.
 pub struct TimezonePage {
-    current_index: usize,
     base_map: Option<Bitmap>,
     offset_images: Vec<Option<Bitmap>>,
+    renderer: FontRenderer,
+    query: String,
+    /// Zones whose name contains the query, in alphabetical order
+    matches: Vec<Tz>,
+    selected_index: usize,
+    scroll_offset: usize,
 }
 
 impl TimezonePage {
-    pub fn new() -> Self {
-        let base_map = Bitmap::load("/home/kramwriter/KramWriter/assets/Timezone/map_clear.bmp").ok();
+    /// Starts with `current` selected.
+    pub fn new(current: Tz) -> Self {
+        let base_map = Bitmap::load(&paths().asset("Timezone/map_clear.bmp")).ok();
         let mut offset_images = Vec::new();
 
         for offset in OFFSETS.iter() {
-            let path = format!("/home/kramwriter/KramWriter/assets/Timezone/{}.bmp", offset);
+            let path = paths().asset(&format!("Timezone/{}.bmp", offset));
             let img = Bitmap::load(&path).ok();
-            
+
             if img.is_none() {
                 println!("⚠️ Timezone asset missing: {}", path);
             }
             offset_images.push(img);
         }
 
-        // Start at UTC (Index 12)
-        Self { 
-            current_index: 12, 
-            base_map, 
-            offset_images 
+        let mut page = Self {
+            base_map,
+            offset_images,
+            renderer: FontRenderer::new(&paths().font("BebasNeue-Regular.ttf")),
+            query: String::new(),
+            matches: Vec::new(),
+            selected_index: 0,
+            scroll_offset: 0,
+        };
+        page.refresh_matches(Some(current));
+        page
+    }
+
+    /// Re-filters the zones, keeping `keep` selected if it still matches.
+    fn refresh_matches(&mut self, keep: Option<Tz>) {
+        let query = self.query.to_lowercase();
+        self.matches = TZ_VARIANTS.iter()
+            .copied()
+            .filter(|tz| display_name(*tz).to_lowercase().contains(&query))
+            .collect();
+        self.matches.sort_by_key(|tz| tz.name());
+
+        self.selected_index = keep
+            .and_then(|tz| self.matches.iter().position(|m| *m == tz))
+            .unwrap_or(0);
+        self.scroll_offset = self.selected_index.saturating_sub(VISIBLE_MATCHES / 2)
+            .min(self.matches.len().saturating_sub(VISIBLE_MATCHES));
+    }
+
+    fn selected(&self) -> Option<Tz> {
+        self.matches.get(self.selected_index).copied()
+    }
+
+    fn draw_search_box(&self, display: &mut SharpDisplay, ctx: &Context) {
+        let height = display.height() - BOX_TOP;
+        display.fill_rect(0, BOX_TOP, display.width(), height, Pixel::White, ctx);
+        for x in 0..display.width() { display.draw_pixel(x, BOX_TOP, Pixel::Black, ctx); }
+
+        let y = (BOX_TOP + 1) as i32;
+        let search = format!("SEARCH: {}_", self.query);
+        self.renderer.draw_text_colored(display, &search, 5, y + 17, 18.0, Pixel::Black, ctx);
+        if let Some(tz) = self.selected() {
+            self.renderer.draw_text_colored(display, &utc_label(tz), 320, y + 17, 18.0, Pixel::Black, ctx);
+        }
+
+        if self.matches.is_empty() {
+            let y = y + ROW_HEIGHT as i32;
+            self.renderer.draw_text_colored(display, "NO MATCHING TIMEZONE", 5, y + 17, 18.0, Pixel::Black, ctx);
+        }
+
+        for i in 0..VISIBLE_MATCHES {
+            let index = self.scroll_offset + i;
+            let Some(tz) = self.matches.get(index) else { break };
+            let row_y = BOX_TOP + 1 + (i + 1) * ROW_HEIGHT;
+            let color = if index == self.selected_index {
+                display.fill_rect(0, row_y, display.width(), ROW_HEIGHT, Pixel::Black, ctx);
+                Pixel::White
+            } else {
+                Pixel::Black
+            };
+            self.renderer.draw_text_colored(display, &display_name(*tz), 5, row_y as i32 + 17, 18.0, color, ctx);
         }
     }
 }
@@ -44,64 +118,89 @@ impl TimezonePage {
 impl Page for TimezonePage {
     fn update(&mut self, key: Key, ctx: &mut Context) -> Action {
         match key {
-            Key::Right => {
-                self.current_index = (self.current_index + 1) % OFFSETS.len();
+            Key::Up => {
+                if self.selected_index > 0 {
+                    self.selected_index -= 1;
+                    if self.selected_index < self.scroll_offset { self.scroll_offset = self.selected_index; }
+                }
                 Action::None
             }
-            Key::Left => {
-                if self.current_index == 0 {
-                    self.current_index = OFFSETS.len() - 1;
-                } else {
-                    self.current_index -= 1;
+            Key::Down => {
+                if self.selected_index + 1 < self.matches.len() {
+                    self.selected_index += 1;
+                    if self.selected_index >= self.scroll_offset + VISIBLE_MATCHES {
+                        self.scroll_offset = self.selected_index + 1 - VISIBLE_MATCHES;
+                    }
                 }
                 Action::None
             }
             // Pressing ENTER saves the selection
             Key::Char('\n') => {
-                let selected_tz = OFFSETS[self.current_index].to_string();
-                ctx.timezone = selected_tz; // Save to global context
-                
-                println!("Timezone saved: {}", ctx.timezone); // Debug print
-                Action::Pop // Return to Settings
+                match self.selected() {
+                    Some(tz) => {
+                        ctx.timezone = tz.name().to_string();
+                        Action::Pop // Return to Settings
+                    }
+                    None => Action::None,
+                }
+            }
+            Key::Backspace => {
+                let keep = self.selected();
+                self.query.pop();
+                self.refresh_matches(keep);
+                Action::None
             }
-            Key::Esc => Action::Pop, // Return without saving (optional)
+            Key::Char(c) if c.is_alphanumeric() || " /_+-".contains(c) => {
+                let keep = self.selected();
+                self.query.push(if c == '_' { ' ' } else { c });
+                self.refresh_matches(keep);
+                Action::None
+            }
+            Key::Esc => Action::Pop, // Return without saving
             _ => Action::None,
         }
     }
-    
-    // ... draw function stays the same ...
-
 
     fn draw(&self, display: &mut SharpDisplay, ctx: &Context) {
         // Draw the static map first
         if let Some(map) = &self.base_map {
-            self.draw_full(display, map, ctx);
+            display.draw_bitmap(0, 0, map, ctx);
         }
 
-        // Overlay the selected timezone (only black pixels for "transparency")
-        if let Some(offset_bmp) = &self.offset_images[self.current_index] {
-            self.draw_transparent(display, offset_bmp, ctx);
+        // Overlay the selected zone's band (only black pixels for "transparency")
+        let band = self.selected()
+            .and_then(|tz| OFFSETS.iter().position(|offset| *offset == offset_name(tz)));
+        if let Some(offset_bmp) = band.and_then(|i| self.offset_images[i].as_ref()) {
+            display.draw_bitmap_colored(0, 0, offset_bmp, Pixel::Black, ctx);
         }
+
+        self.draw_search_box(display, ctx);
     }
 }
 
-impl TimezonePage {
-    fn draw_full(&self, display: &mut SharpDisplay, bmp: &Bitmap, ctx: &Context) {
-        for y in 0..bmp.height.min(240) {
-            for x in 0..bmp.width.min(400) {
-                display.draw_pixel(x, y, bmp.pixels[y * bmp.width + x], ctx);
-            }
-        }
-    }
+/// The zone's name as searched and shown, e.g. "America/New York".
+fn display_name(tz: Tz) -> String {
+    tz.name().replace('_', " ")
+}
 
-    fn draw_transparent(&self, display: &mut SharpDisplay, bmp: &Bitmap, ctx: &Context) {
-        for y in 0..bmp.height.min(240) {
-            for x in 0..bmp.width.min(400) {
-                let pixel = bmp.pixels[y * bmp.width + x];
-                if pixel == Pixel::Black {
-                    display.draw_pixel(x, y, pixel, ctx);
-                }
-            }
-        }
+/// Seconds east of UTC in `tz` right now, taking DST into account.
+fn utc_offset(tz: Tz) -> i32 {
+    tz.offset_from_utc_datetime(&Utc::now().naive_utc()).fix().local_minus_utc()
+}
+
+/// The current offset as named in OFFSETS, e.g. "-3.5" or "UTC".
+fn offset_name(tz: Tz) -> String {
+    match utc_offset(tz) {
+        0 => String::from("UTC"),
+        seconds if seconds % 3600 == 0 => (seconds / 3600).to_string(),
+        seconds => format!("{:.1}", seconds as f32 / 3600.0),
     }
-}
\ No newline at end of file
+}
+
+/// The current offset for the search box, e.g. "UTC+05:30".
+fn utc_label(tz: Tz) -> String {
+    let seconds = utc_offset(tz);
+    let sign = if seconds < 0 { '-' } else { '+' };
+    let minutes = seconds.abs() / 60;
+    format!("UTC{}{:02}:{:02}", sign, minutes / 60, minutes % 60)
+}
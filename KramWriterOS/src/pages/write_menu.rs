@@ -5,6 +5,7 @@ use crate::ui::bitmap::Bitmap;
 use termion::event::Key;
 use rpi_memory_display::Pixel;
 use crate::pages::file_browser::{FileBrowserPage, BrowserMode};
+use crate::paths::paths;
 
 const NEW_FILE_Y: i32 = 40; 
 const OPEN_FILE_Y: i32 = 90;
@@ -18,7 +19,7 @@ pub struct WriteMenuPage {
 
 impl WriteMenuPage {
     pub fn new() -> Self {
-        let asset_path = "/home/kramwriter/KramWriter/assets/Writing/menu";
+        let asset_path = paths().asset("Writing/menu");
         Self {
             current_index: 0,
             title: Bitmap::load(&format!("{}/title.bmp", asset_path)).ok(),
@@ -35,16 +36,7 @@ impl WriteMenuPage {
 
     // Helper to draw bitmaps with vertical offsets
     fn draw_layer(&self, display: &mut SharpDisplay, bmp: &Bitmap, y_offset: i32, ctx: &Context) {
-        for y in 0..bmp.height {
-            let screen_y = y as i32 + y_offset;
-            if screen_y >= 0 && screen_y < 240 {
-                for x in 0..bmp.width.min(400) {
-                    if bmp.pixels[y * bmp.width + x] == Pixel::Black {
-                        display.draw_pixel(x, screen_y as usize, Pixel::Black, ctx);
-                    }
-                }
-            }
-        }
+        display.draw_bitmap_colored(0, y_offset, bmp, Pixel::Black, ctx);
     }
 }
 
@@ -3,6 +3,7 @@ use crate::context::Context;
 use crate::display::SharpDisplay;
 use crate::ui::bitmap::Bitmap;
 use crate::ui::fonts::FontRenderer;
+use crate::paths::paths;
 use termion::event::Key;
 use rpi_memory_display::Pixel;
 use rand::seq::SliceRandom;
@@ -64,8 +65,8 @@ pub struct ZeugtrisPage {
 
 impl ZeugtrisPage {
     pub fn new() -> Self {
-        let asset_path = "/home/kramwriter/KramWriter/assets/zeugtris/game";
-        let renderer = FontRenderer::new("/home/kramwriter/KramWriter/fonts/BebasNeue-Regular.ttf");
+        let asset_path = paths().asset("zeugtris/game");
+        let renderer = FontRenderer::new(&paths().font("BebasNeue-Regular.ttf"));
         
         let mut sprites = HashMap::new();
         let mut stats = HashMap::new();
@@ -242,11 +243,10 @@ impl ZeugtrisPage {
     fn trigger_game_over(&mut self) {
         self.game_over = true;
         
-        let path = "/home/kramwriter/KramWriter/assets/zeugtris/highscores.txt";
         let mut scores = Vec::new();
 
         // Read existing scores
-        if let Ok(file) = File::open(path) {
+        if let Ok(file) = File::open(paths().saved_highscores()) {
             let reader = BufReader::new(file);
             for line in reader.lines().map_while(Result::ok) {
                 if let Ok(val) = line.trim().parse::<u32>() {
@@ -261,6 +261,8 @@ impl ZeugtrisPage {
         scores.truncate(10);
 
         // Write back out
+        let path = paths().highscores();
+        let _ = std::fs::create_dir_all(&paths().data);
         if let Ok(mut file) = OpenOptions::new().write(true).create(true).truncate(true).open(path) {
             for score in scores {
                 let _ = writeln!(file, "{}", score);
@@ -391,39 +393,23 @@ impl Page for ZeugtrisPage {
 
         if self.game_over {
             if let Some(bmp) = &self.gameover_bmp {
-                let x_off = (400 - bmp.width as i32) / 2;
-                let y_off = (240 - bmp.height as i32) / 2;
-                for y in 0..bmp.height {
-                    for x in 0..bmp.width {
-                        if bmp.pixels[y * bmp.width + x] == Pixel::Black {
-                            let px = (x as i32 + x_off) as usize;
-                            let py = (y as i32 + y_off) as usize;
-                            if px < 400 && py < 240 {
-                                display.draw_pixel(px, py, Pixel::Black, ctx);
-                            }
-                        }
-                    }
-                }
+                let x_off = (display.width() as i32 - bmp.width as i32) / 2;
+                let y_off = (display.height() as i32 - bmp.height as i32) / 2;
+                display.draw_bitmap_colored(x_off, y_off, bmp, Pixel::Black, ctx);
             }
 
             // Draw center score
             let score_text = format!("{}", self.score);
             let score_size = 42.0;
             let score_width = self.renderer.calculate_width(&score_text, score_size);
-            let start_x = 200 - (score_width / 2);
+            let start_x = display.width() as i32 / 2 - (score_width / 2);
             self.renderer.draw_text(display, &score_text, start_x, 165, score_size, ctx);
             
             return;
         }
 
         if let Some(bmp) = &self.backdrop {
-            for y in 0..(bmp.height as usize).min(240) {
-                for x in 0..(bmp.width as usize).min(400) {
-                    if bmp.pixels[y * bmp.width as usize + x] == Pixel::Black {
-                        display.draw_pixel(x, y, Pixel::Black, ctx);
-                    }
-                }
-            }
+            display.draw_bitmap_colored(0, 0, bmp, Pixel::Black, ctx);
         }
 
         for r in 0..GRID_HEIGHT {
@@ -493,7 +479,7 @@ impl Page for ZeugtrisPage {
                                     if pixel == Pixel::Black {
                                         let final_x = screen_x + px;
                                         let final_y = screen_y + py;
-                                        if final_x < 400 && final_y < 240 {
+                                        if final_x < display.width() && final_y < display.height() {
                                             display.draw_pixel(final_x, final_y, Pixel::Black, ctx);
                                         }
                                     }
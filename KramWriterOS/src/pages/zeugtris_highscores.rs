@@ -3,6 +3,7 @@ use crate::context::Context;
 use crate::display::SharpDisplay;
 use crate::ui::bitmap::Bitmap;
 use crate::ui::fonts::FontRenderer;
+use crate::paths::paths;
 use termion::event::Key;
 use rpi_memory_display::Pixel;
 use std::fs::File;
@@ -16,13 +17,11 @@ pub struct ZeugtrisHighscoresPage {
 
 impl ZeugtrisHighscoresPage {
     pub fn new() -> Self {
-        let renderer = FontRenderer::new("/home/kramwriter/KramWriter/fonts/BebasNeue-Regular.ttf");
-        let title_bmp = Bitmap::load("/home/kramwriter/KramWriter/assets/zeugtris/highscores/highscores.bmp").ok();
+        let renderer = FontRenderer::new(&paths().font("BebasNeue-Regular.ttf"));
+        let title_bmp = Bitmap::load(&paths().asset("zeugtris/highscores/highscores.bmp")).ok();
 
         let mut scores = Vec::new();
-        let path = "/home/kramwriter/KramWriter/assets/zeugtris/highscores.txt";
-        
-        if let Ok(file) = File::open(path) {
+        if let Ok(file) = File::open(paths().saved_highscores()) {
             let reader = BufReader::new(file);
             for line in reader.lines().map_while(Result::ok) {
                 if let Ok(val) = line.trim().parse::<u32>() {
@@ -58,20 +57,9 @@ impl Page for ZeugtrisHighscoresPage {
 
         // Draw title bitmap centered horizontally
         if let Some(bmp) = &self.title_bmp {
-            let x_off = (400 - bmp.width as i32) / 2;
+            let x_off = (display.width() as i32 - bmp.width as i32) / 2;
             let y_off = 15; // Padding from top
-            
-            for y in 0..bmp.height {
-                for x in 0..bmp.width {
-                    if bmp.pixels[y * bmp.width + x] == Pixel::Black {
-                        let px = (x as i32 + x_off) as usize;
-                        let py = y + y_off;
-                        if px < 400 && py < 240 {
-                            display.draw_pixel(px, py, Pixel::Black, ctx);
-                        }
-                    }
-                }
-            }
+            display.draw_bitmap_colored(x_off, y_off, bmp, Pixel::Black, ctx);
         }
 
         // Display Scores in a two-column layout
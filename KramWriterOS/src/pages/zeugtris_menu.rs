@@ -8,6 +8,7 @@ use rpi_memory_display::Pixel;
 // --- Updated imports to match your file names ---
 use crate::pages::zeugtris::ZeugtrisPage;
 use crate::pages::zeugtris_highscores::ZeugtrisHighscoresPage;
+use crate::paths::paths;
 
 pub struct ZeugtrisMenuPage {
     current_index: usize, 
@@ -18,7 +19,7 @@ pub struct ZeugtrisMenuPage {
 impl ZeugtrisMenuPage {
     pub fn new() -> Self {
         // Path to your Zeugtris assets
-        let asset_path = "/home/kramwriter/KramWriter/assets/zeugtris/menu";
+        let asset_path = paths().asset("zeugtris/menu");
         
         Self {
             current_index: 0,
@@ -31,14 +32,7 @@ impl ZeugtrisMenuPage {
     }
 
     fn draw_full_screen(&self, display: &mut SharpDisplay, bmp: &Bitmap, ctx: &Context) {
-        for y in 0..bmp.height.min(240) {
-            for x in 0..bmp.width.min(400) {
-                let pixel = bmp.pixels[y * bmp.width + x];
-                if pixel == Pixel::Black {
-                    display.draw_pixel(x, y, Pixel::Black, ctx);
-                }
-            }
-        }
+        display.draw_bitmap_colored(0, 0, bmp, Pixel::Black, ctx);
     }
 }
 
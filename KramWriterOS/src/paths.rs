@@ -0,0 +1,228 @@
+//! Where KramWriterOS finds its art, fonts and scripts, and where it
+//! keeps the user's documents and its own data.
+//!
+//! Each location is taken from the first of:
+//!
+//! 1. an environment variable (`KRAMWRITER_ASSETS`, `KRAMWRITER_FONTS`,
+//!    `KRAMWRITER_SCRIPTS`, `KRAMWRITER_DOCUMENTS`, `KRAMWRITER_DATA`,
+//!    or `KRAMWRITER_ROOT` for the checkout holding assets, fonts and
+//!    scripts),
+//! 2. the config file (`$KRAMWRITER_CONFIG`, by default
+//!    `$XDG_CONFIG_HOME/kramwriter/kramwriter.conf`), with lines like
+//!    `root = ~/KramWriter` using the same keys in lower case,
+//! 3. the defaults: the KramWriter checkout the binary was built in
+//!    (or `$XDG_DATA_HOME/kramwriter`), `~/folder` for documents and
+//!    `$XDG_DATA_HOME/kramwriter` for data.
+//!
+//! Relative paths in the config file are relative to the file, and
+//! `~/` is expanded everywhere. The config file also holds the unit's
+//! setup, see `device`.
+
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+static PATHS: OnceLock<Paths> = OnceLock::new();
+
+/// Fonts for characters Inter lacks, where Raspberry Pi OS installs
+/// them: fonts-noto-cjk for Japanese, Chinese and Korean, and
+/// fonts-dejavu-core for math and other symbols.
+const FALLBACK_FONTS: [&str; 2] = [
+    "/usr/share/fonts/opentype/noto/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Paths {
+    /// Bitmaps for the pages
+    pub assets: PathBuf,
+    /// TrueType fonts
+    pub fonts: PathBuf,
+    /// Helper scripts, e.g. the Simplenote sync
+    pub scripts: PathBuf,
+    /// The user's documents. The file browsers start here and can't
+    /// go above it.
+    pub documents: PathBuf,
+    /// Files written by the app itself, e.g. settings and Zeugtris
+    /// highscores
+    pub data: PathBuf,
+}
+
+/// The paths for this run, resolved from the environment on first use.
+pub fn paths() -> &'static Paths {
+    PATHS.get_or_init(|| Paths::resolve(|name| env::var(name).ok(), env::current_exe().ok()))
+}
+
+/// Uses `paths` for this run instead of resolving them, e.g. in tests.
+/// Fails (returning them) if the paths are already in use.
+pub fn set(paths: Paths) -> Result<(), Paths> {
+    PATHS.set(paths)
+}
+
+impl Paths {
+    /// Everything but the documents and data in a KramWriter checkout.
+    pub fn for_checkout(root: &Path, documents: PathBuf, data: PathBuf) -> Self {
+        Self {
+            assets: root.join("assets"),
+            fonts: root.join("fonts"),
+            scripts: root.join("KramWriterOS/scripts"),
+            documents,
+            data,
+        }
+    }
+
+    /// Resolves the paths from environment variables (looked up with
+    /// `var`), the config file and the defaults. `exe` is the running
+    /// binary, used to find the checkout it was built in.
+    pub fn resolve(var: impl Fn(&str) -> Option<String>, exe: Option<PathBuf>) -> Self {
+        let home = home(&var);
+        let data_home = var("XDG_DATA_HOME").map(PathBuf::from).unwrap_or_else(|| home.join(".local/share"));
+
+        let config_path = config_path(&var, &home);
+        let config = read_config(&config_path);
+        let config_dir = config_path.parent().unwrap_or(Path::new("/"));
+
+        let get = |key: &str| {
+            if let Some(value) = var(&format!("KRAMWRITER_{}", key.to_uppercase())) {
+                Some(expand(&value, &home, None))
+            } else {
+                config.get(key).map(|value| expand(value, &home, Some(config_dir)))
+            }
+        };
+
+        let root = get("root")
+            .or_else(|| exe.as_deref().and_then(find_checkout))
+            .unwrap_or_else(|| data_home.join("kramwriter"));
+        let defaults = Self::for_checkout(&root, home.join("folder"), data_home.join("kramwriter"));
+        Self {
+            assets: get("assets").unwrap_or(defaults.assets),
+            fonts: get("fonts").unwrap_or(defaults.fonts),
+            scripts: get("scripts").unwrap_or(defaults.scripts),
+            documents: get("documents").unwrap_or(defaults.documents),
+            data: get("data").unwrap_or(defaults.data),
+        }
+    }
+
+    /// An asset, e.g. `asset("Menu/Write_0.bmp")`
+    pub fn asset(&self, name: &str) -> String {
+        self.assets.join(name).to_string_lossy().into_owned()
+    }
+
+    /// A font file, e.g. `font("BebasNeue-Regular.ttf")`
+    pub fn font(&self, name: &str) -> String {
+        self.fonts.join(name).to_string_lossy().into_owned()
+    }
+
+    /// The font chain for the text being written or learned: Inter, then
+    /// the FALLBACK_FONTS that are installed. A copy in the fonts folder
+    /// is used before the system's.
+    pub fn text_fonts(&self) -> Vec<String> {
+        let fallbacks = FALLBACK_FONTS.iter().map(Path::new).filter_map(|system| {
+            let copy = self.fonts.join(system.file_name()?);
+            [copy, system.to_path_buf()].into_iter().find(|path| path.exists())
+        });
+        let mut fonts = vec![self.font("Inter_28pt-Medium.ttf")];
+        fonts.extend(fallbacks.map(|path| path.to_string_lossy().into_owned()));
+        fonts
+    }
+
+    /// Where Zeugtris highscores are saved
+    pub fn highscores(&self) -> PathBuf {
+        self.data.join("zeugtris_highscores.txt")
+    }
+
+    /// Where Zeugtris highscores are read from. Older versions kept
+    /// them with the assets; those are used until a new score is saved.
+    pub fn saved_highscores(&self) -> PathBuf {
+        existing_or(self.highscores(), self.assets.join("zeugtris/highscores.txt"))
+    }
+
+    /// Where the settings are saved
+    pub fn settings(&self) -> PathBuf {
+        self.data.join("settings.json")
+    }
+
+    /// Keymap files for more keyboard layouts, e.g. `neo.keymap`
+    pub fn keymaps(&self) -> PathBuf {
+        self.data.join("keymaps")
+    }
+
+    /// Where F12 screenshots are saved
+    pub fn screenshots(&self) -> PathBuf {
+        self.data.join("screenshots")
+    }
+
+    /// Where the Simplenote login is saved
+    pub fn simplenote_creds(&self) -> PathBuf {
+        self.data.join("simplenote_creds")
+    }
+
+    /// The folder Simplenote notes are synced into
+    pub fn simplenote_notes(&self) -> PathBuf {
+        self.documents.join("simplenote")
+    }
+}
+
+/// Returns `path` unless only `legacy` exists.
+fn existing_or(path: PathBuf, legacy: PathBuf) -> PathBuf {
+    if !path.exists() && legacy.exists() { legacy } else { path }
+}
+
+fn home(var: &impl Fn(&str) -> Option<String>) -> PathBuf {
+    PathBuf::from(var("HOME").unwrap_or_else(|| String::from("/")))
+}
+
+/// `$KRAMWRITER_CONFIG`, or `kramwriter/kramwriter.conf` in the XDG
+/// config directory.
+fn config_path(var: &impl Fn(&str) -> Option<String>, home: &Path) -> PathBuf {
+    let config_home = var("XDG_CONFIG_HOME").map(PathBuf::from).unwrap_or_else(|| home.join(".config"));
+    var("KRAMWRITER_CONFIG")
+        .map(|path| expand(&path, home, None))
+        .unwrap_or_else(|| config_home.join("kramwriter/kramwriter.conf"))
+}
+
+/// The settings in the config file, none if it can't be read.
+fn read_config(path: &Path) -> HashMap<String, String> {
+    fs::read_to_string(path).map(|text| parse_config(&text)).unwrap_or_default()
+}
+
+/// Looks up a setting that isn't a path, e.g. `config_value(var,
+/// "disp_pin")`: `KRAMWRITER_DISP_PIN`, else `disp_pin` in the config
+/// file.
+pub(crate) fn config_value(var: impl Fn(&str) -> Option<String>, key: &str) -> Option<String> {
+    var(&format!("KRAMWRITER_{}", key.to_uppercase()))
+        .or_else(|| read_config(&config_path(&var, &home(&var))).remove(key))
+}
+
+/// Parses `key = value` lines, skipping blank lines and `#` comments.
+fn parse_config(text: &str) -> HashMap<String, String> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, value)| (key.trim().to_lowercase(), value.trim().to_string()))
+        .collect()
+}
+
+/// Expands a leading `~/` to `home` and makes relative paths relative
+/// to `base`, if given.
+fn expand(path: &str, home: &Path, base: Option<&Path>) -> PathBuf {
+    if path == "~" {
+        home.to_path_buf()
+    } else if let Some(rest) = path.strip_prefix("~/") {
+        home.join(rest)
+    } else {
+        base.map_or_else(|| PathBuf::from(path), |base| base.join(path))
+    }
+}
+
+/// Finds the KramWriter checkout holding `exe` (e.g. a binary in
+/// `KramWriterOS/target/release`), recognised by its assets and fonts.
+fn find_checkout(exe: &Path) -> Option<PathBuf> {
+    exe.ancestors()
+        .skip(1)
+        .find(|dir| dir.join("assets").is_dir() && dir.join("fonts").is_dir())
+        .map(Path::to_path_buf)
+}
@@ -0,0 +1,148 @@
+//! Saving the Context between runs.
+//!
+//! Settings live in `settings.json` in the data directory, as the
+//! serialized Context plus a `version` key:
+//!
+//! ```json
+//! { "version": 2, "dark_mode": true, "timezone": "Europe/Berlin", ... }
+//! ```
+//!
+//! Adding a field to Context needs nothing here: older files simply
+//! don't have the key and get the default. Renaming a field or
+//! changing what a value means does need a migration: bump `VERSION`
+//! and append a function to `MIGRATIONS` that rewrites the old keys.
+
+use crate::context::Context;
+use serde_json::{Map, Value};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// The version written to new settings files.
+pub const VERSION: u64 = 2;
+
+/// A migration rewrites the settings of one version into the next.
+type Migration = fn(&mut Map<String, Value>);
+
+/// `MIGRATIONS[i]` upgrades version `i + 1` to `i + 2`.
+const MIGRATIONS: &[Migration] = &[offsets_to_zones];
+
+/// Version 1 kept the timezone as an hour offset like "-5" or "5.5".
+/// Whole hours become the fixed "Etc/GMT" zones (whose signs are
+/// inverted), so the clock stays the same until a named zone with DST
+/// is chosen. Half hours become the main zone using that offset.
+fn offsets_to_zones(settings: &mut Map<String, Value>) {
+    let Some(Value::String(timezone)) = settings.get_mut("timezone") else { return };
+    let zone = match timezone.as_str() {
+        "-3.5" => "America/St_Johns",
+        "3.5" => "Asia/Tehran",
+        "4.5" => "Asia/Kabul",
+        "5.5" => "Asia/Kolkata",
+        "6.5" => "Asia/Yangon",
+        "9.5" => "Australia/Darwin",
+        "10.5" => "Australia/Lord_Howe",
+        hours => match hours.parse::<i32>() {
+            Ok(hours) => {
+                *timezone = format!("Etc/GMT{:+}", -hours);
+                return;
+            }
+            // "UTC", or already a zone name
+            Err(_) => return,
+        },
+    };
+    *timezone = String::from(zone);
+}
+
+/// Loads the Context from a settings file and saves it when it changes.
+pub struct SettingsStore {
+    path: PathBuf,
+    /// The settings as last read or written, to skip saving when
+    /// nothing has changed.
+    saved: Value,
+    /// False for files written by a newer version, which are used but
+    /// never overwritten.
+    writable: bool,
+}
+
+impl SettingsStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            saved: Value::Null,
+            writable: true,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Reads the Context, migrating it to the current version. Missing
+    /// keys, and keys whose value can't be used (e.g. an unknown layout),
+    /// get their defaults; the other settings are kept. A missing or
+    /// unreadable file gives the default Context, and is left alone
+    /// until a setting changes.
+    pub fn load(&mut self) -> Context {
+        let settings = fs::read_to_string(&self.path)
+            .ok()
+            .and_then(|text| serde_json::from_str::<Map<String, Value>>(&text).ok())
+            .map(|settings| self.migrate(settings))
+            .unwrap_or_default();
+        let ctx = from_settings(settings);
+        self.saved = to_settings(&ctx);
+        ctx
+    }
+
+    /// Writes `ctx` if it differs from what was last loaded or saved.
+    pub fn save(&mut self, ctx: &Context) -> io::Result<()> {
+        let settings = to_settings(ctx);
+        if !self.writable || settings == self.saved {
+            return Ok(());
+        }
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        // Write next to the file and rename over it, so a power cut
+        // can't leave half a settings file behind.
+        let partial = self.path.with_extension("json.partial");
+        fs::write(&partial, serde_json::to_string_pretty(&settings)?)?;
+        fs::rename(&partial, &self.path)?;
+        self.saved = settings;
+        Ok(())
+    }
+
+    fn migrate(&mut self, mut settings: Map<String, Value>) -> Map<String, Value> {
+        let version = settings.get("version").and_then(Value::as_u64).unwrap_or(1);
+        if version > VERSION {
+            self.writable = false;
+        }
+        for migration in MIGRATIONS.iter().skip(version.saturating_sub(1) as usize) {
+            migration(&mut settings);
+        }
+        settings
+    }
+}
+
+/// The Context from the keys of a settings file, taking them one at a
+/// time so that a bad value only loses its own setting.
+fn from_settings(settings: Map<String, Value>) -> Context {
+    let Value::Object(mut usable) = to_settings(&Context::default()) else { unreachable!() };
+    for (key, value) in settings {
+        let mut with_key = usable.clone();
+        with_key.insert(key, value);
+        if serde_json::from_value::<Context>(Value::Object(with_key.clone())).is_ok() {
+            usable = with_key;
+        }
+    }
+    serde_json::from_value(Value::Object(usable)).unwrap_or_default()
+}
+
+/// The settings file contents for `ctx`.
+fn to_settings(ctx: &Context) -> Value {
+    let mut settings = Map::new();
+    settings.insert(String::from("version"), Value::from(VERSION));
+    if let Ok(Value::Object(fields)) = serde_json::to_value(ctx) {
+        settings.extend(fields);
+    }
+    Value::Object(settings)
+}
@@ -1,4 +1,5 @@
-use rpi_memory_display::Pixel;
+use rpi_memory_display::dither::Dither;
+use rpi_memory_display::{Image, Pixel};
 use anyhow::Result;
 use std::fs;
 
@@ -6,15 +7,25 @@ pub struct Bitmap {
     pub width: usize,
     pub height: usize,
     pub pixels: Vec<Pixel>,
+    /// The same pixels packed for blitting to the display.
+    pub image: Image,
 }
 
 impl Bitmap {
+    /// Loads a BMP, thresholding colour images at half brightness.
+    /// Good for UI art that is already black and white.
     pub fn load(path: &str) -> Result<Self> {
+        Self::load_dithered(path, Dither::default())
+    }
+
+    /// Loads a BMP, reducing colour images to black and white with the
+    /// given dithering. Use error diffusion (e.g. Atkinson) for photos.
+    pub fn load_dithered(path: &str, dither: Dither) -> Result<Self> {
         let data = fs::read(path)?;
-        Self::parse(&data).ok_or_else(|| anyhow::anyhow!("Failed to parse BMP: {}", path))
+        Self::parse(&data, dither).ok_or_else(|| anyhow::anyhow!("Failed to parse BMP: {}", path))
     }
 
-    fn parse(data: &[u8]) -> Option<Self> {
+    fn parse(data: &[u8], dither: Dither) -> Option<Self> {
         if data.len() < 54 || &data[0..2] != b"BM" { return None; }
 
         let pixel_offset = u32::from_le_bytes(data[10..14].try_into().ok()?) as usize;
@@ -22,7 +33,8 @@ impl Bitmap {
         let height = i32::from_le_bytes(data[22..26].try_into().ok()?) as usize;
         let bpp = u16::from_le_bytes(data[28..30].try_into().ok()?);
 
-        let mut pixels = Vec::with_capacity(width * height);
+        // Brightness per pixel (0 = black, 255 = white), dithered below
+        let mut gray = Vec::with_capacity(width * height);
 
         match bpp {
             1 => {
@@ -33,7 +45,7 @@ impl Bitmap {
                         let byte = data[row_start + (x / 8)];
                         let bit = 7 - (x % 8);
                         // Standard: 1 is Black (ink), 0 is White (paper)
-                        pixels.push(if (byte >> bit) & 1 == 1 { Pixel::Black } else { Pixel::White });
+                        gray.push(if (byte >> bit) & 1 == 1 { 0 } else { 255 });
                     }
                 }
             }
@@ -52,16 +64,22 @@ impl Bitmap {
                         // Check Alpha channel for 32-bit images. 
                         // If alpha is 0 (transparent), treat as White (background).
                         if bpp == 32 && data[px_start + 3] == 0 {
-                            pixels.push(Pixel::White);
+                            gray.push(255);
                         } else {
-                            let brightness = (r + g + b) / 3;
-                            pixels.push(if brightness > 127 { Pixel::White } else { Pixel::Black });
+                            gray.push(((r + g + b) / 3) as u8);
                         }
                     }
                 }
             }
             _ => return None,
         }
-        Some(Self { width, height, pixels })
+        let pixels = dither.apply(&gray, width, height);
+        let mut image = Image::new(width, height);
+        for (i, pixel) in pixels.iter().enumerate() {
+            if *pixel == Pixel::Black {
+                image.set_pixel(i % width, i / width, Pixel::Black);
+            }
+        }
+        Some(Self { width, height, pixels, image })
     }
 }
\ No newline at end of file
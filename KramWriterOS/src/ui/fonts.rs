@@ -1,18 +1,335 @@
-use fontdue::{Font, FontSettings};
+use fontdue::{Font, FontSettings, Metrics};
+use lru::LruCache;
+use parking_lot::Mutex;
+use rpi_memory_display::dither::Dither;
+use std::collections::HashMap;
 use std::fs;
+use std::hash::Hash;
+use std::num::NonZeroUsize;
+use std::sync::{Arc, OnceLock};
+use ttf_parser::gpos::{PairAdjustment, PositioningSubtable};
+use ttf_parser::{Face, Tag};
 use crate::display::SharpDisplay;
 use crate::context::Context;
-use rpi_memory_display::Pixel;
+use rpi_memory_display::{Image, Pixel};
 
+/// Glyphs kept rasterized. A screen of editor text uses around a
+/// hundred per font size, and a glyph is a few hundred bytes.
+pub const GLYPH_CACHE_SIZE: usize = 2048;
+
+/// Pen positions are kept to a quarter pixel, so each glyph is drawn
+/// at one of this many horizontal offsets.
+const SUBPIXEL_STEPS: u8 = 4;
+
+/// A rasterized character: coverage from 0 to 255, in rows from the top.
+pub struct Glyph {
+    pub metrics: Metrics,
+    pub coverage: Vec<u8>,
+}
+
+/// Which font, character, size and subpixel offset a glyph was
+/// rasterized for. The size is kept as bits, as f32 isn't hashable.
+type GlyphKey = (usize, char, u32, u8);
+
+type GlyphCache = Arc<Mutex<LruCache<GlyphKey, Arc<Glyph>>>>;
+
+/// A glyph as drawn in one coverage mode: its black pixels, with the
+/// top left corner this far from the pen on the baseline.
+struct Mask {
+    left: i32,
+    top: i32,
+    image: Image,
+}
+
+/// The glyph, the coverage mode, and for ordered dithering where the
+/// pen sits in the pattern, as the pattern is aligned to the screen.
+type MaskKey = (GlyphKey, Dither, (u8, u8));
+
+type MaskCache = Arc<Mutex<LruCache<MaskKey, Arc<Mask>>>>;
+
+/// Fonts loaded by path, shared by all pages, and the glyphs they
+/// rasterized most recently.
+pub struct FontRegistry {
+    fonts: Mutex<HashMap<String, FontRenderer>>,
+    glyphs: GlyphCache,
+    masks: MaskCache,
+}
+
+impl FontRegistry {
+    /// A registry keeping up to `glyphs` rasterized glyphs, and as many
+    /// of them ready to draw.
+    pub fn new(glyphs: usize) -> Self {
+        let capacity = NonZeroUsize::new(glyphs).unwrap_or(NonZeroUsize::MIN);
+        Self {
+            fonts: Mutex::new(HashMap::new()),
+            glyphs: Arc::new(Mutex::new(LruCache::new(capacity))),
+            masks: Arc::new(Mutex::new(LruCache::new(capacity))),
+        }
+    }
+
+    /// The registry used by `FontRenderer::new`.
+    pub fn global() -> &'static FontRegistry {
+        static GLOBAL: OnceLock<FontRegistry> = OnceLock::new();
+        GLOBAL.get_or_init(|| FontRegistry::new(GLYPH_CACHE_SIZE))
+    }
+
+    /// Returns a renderer for the font file at `path`, reading and
+    /// parsing it only the first time.
+    pub fn load(&self, path: &str) -> anyhow::Result<FontRenderer> {
+        let key = fs::canonicalize(path).map_or_else(|_| path.to_string(), |p| p.to_string_lossy().into_owned());
+        let mut fonts = self.fonts.lock();
+        if let Some(renderer) = fonts.get(&key) {
+            return Ok(renderer.clone());
+        }
+        let font_data = fs::read(path)?;
+        let font = Font::from_bytes(&font_data[..], FontSettings::default()).map_err(anyhow::Error::msg)?;
+        let renderer = FontRenderer {
+            font: Arc::new(font),
+            id: fonts.len(),
+            kerning: Arc::new(Kerning::new(font_data)),
+            fallbacks: Vec::new(),
+            glyphs: self.glyphs.clone(),
+            masks: self.masks.clone(),
+            coverage: Dither::default(),
+        };
+        fonts.insert(key, renderer.clone());
+        Ok(renderer)
+    }
+
+    /// Returns a renderer for the first font in `paths`, falling back to
+    /// the others in order for characters it lacks. Fallbacks that can't
+    /// be loaded, e.g. because they aren't installed, are left out.
+    pub fn load_chain(&self, paths: &[impl AsRef<str>]) -> anyhow::Result<FontRenderer> {
+        let Some((first, fallbacks)) = paths.split_first() else {
+            anyhow::bail!("no fonts given");
+        };
+        // Nothing is printed for missing fallbacks, as that would show
+        // through the terminal backend's screen
+        let fallbacks = fallbacks.iter().filter_map(|path| self.load(path.as_ref()).ok());
+        Ok(self.load(first.as_ref())?.with_fallbacks(fallbacks))
+    }
+
+    /// Number of glyphs currently cached.
+    pub fn cached_glyphs(&self) -> usize {
+        self.glyphs.lock().len()
+    }
+
+    /// Number of glyphs currently cached ready to draw.
+    pub fn cached_masks(&self) -> usize {
+        self.masks.lock().len()
+    }
+}
+
+/// Pair kerning of one font, looked up on first use of each pair.
+///
+/// fontdue only reads the old `kern` table, while most current fonts
+/// (Inter and Bebas Neue included) kern in GPOS, so that is tried first.
+struct Kerning {
+    data: Vec<u8>,
+    /// Adjustments in font units
+    pairs: Mutex<HashMap<(char, char), i16>>,
+}
+
+impl Kerning {
+    fn new(data: Vec<u8>) -> Self {
+        Self { data, pairs: Mutex::new(HashMap::new()) }
+    }
+
+    /// How much closer (if negative) `right` goes after `left`, in font units.
+    fn get(&self, font: &Font, left: char, right: char) -> i16 {
+        if let Some(units) = self.pairs.lock().get(&(left, right)) {
+            return *units;
+        }
+        let units = Face::from_slice(&self.data, 0)
+            .ok()
+            .and_then(|face| gpos_kerning(&face, left, right))
+            .or_else(|| font.horizontal_kern(left, right, font.units_per_em()).map(|units| units.round() as i16))
+            .unwrap_or(0);
+        self.pairs.lock().insert((left, right), units);
+        units
+    }
+}
+
+/// The advance adjustment of the pair in the lookups of the font's
+/// `kern` feature.
+fn gpos_kerning(face: &Face, left: char, right: char) -> Option<i16> {
+    let gpos = face.tables().gpos?;
+    let (left, right) = (face.glyph_index(left)?, face.glyph_index(right)?);
+    let kern = Tag::from_bytes(b"kern");
+    let lookups = gpos.features.into_iter().filter(|feature| feature.tag == kern).flat_map(|feature| feature.lookup_indices);
+
+    for index in lookups {
+        let Some(lookup) = gpos.lookups.get(index) else { continue };
+        for subtable in lookup.subtables.into_iter::<PositioningSubtable>() {
+            let PositioningSubtable::Pair(pair) = subtable else { continue };
+            let values = match pair {
+                PairAdjustment::Format1 { coverage, sets } => {
+                    coverage.get(left).and_then(|i| sets.get(i)).and_then(|set| set.get(right))
+                }
+                // Class kerning covers every pair once the first glyph is
+                // covered, most of them with class 0 and no adjustment.
+                PairAdjustment::Format2 { coverage, classes, matrix } if coverage.contains(left) => {
+                    matrix.get((classes.0.get(left), classes.1.get(right)))
+                }
+                PairAdjustment::Format2 { .. } => None,
+            };
+            if let Some((first, _)) = values {
+                return Some(first.x_advance);
+            }
+        }
+    }
+    None
+}
+
+/// Draws text in one font, or a chain of fonts where each character
+/// comes from the first one that has it. Cheap to clone: renderers for
+/// the same file share the parsed font, its kerning and its cached glyphs.
+#[derive(Clone)]
 pub struct FontRenderer {
-    pub font: Font,
+    /// The first font of the chain
+    pub font: Arc<Font>,
+    /// Index of the font in its registry
+    id: usize,
+    kerning: Arc<Kerning>,
+    /// The rest of the chain, in order; these have no fallbacks of their own
+    fallbacks: Vec<FontRenderer>,
+    glyphs: GlyphCache,
+    masks: MaskCache,
+    /// How the glyphs' coverage is turned into black and white pixels
+    coverage: Dither,
 }
 
 impl FontRenderer {
+    /// The font at `path` from the global registry.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the font can't be read or parsed.
     pub fn new(path: &str) -> Self {
-        let font_data = fs::read(path).expect("Failed to read font file");
-        let font = Font::from_bytes(font_data, FontSettings::default()).expect("Failed to parse font");
-        Self { font }
+        FontRegistry::global()
+            .load(path)
+            .unwrap_or_else(|err| panic!("Failed to load font {}: {}", path, err))
+    }
+
+    /// The fonts at `paths` from the global registry, as a chain (see
+    /// `FontRegistry::load_chain`).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the first font can't be read or parsed.
+    pub fn chain(paths: &[impl AsRef<str>]) -> Self {
+        FontRegistry::global()
+            .load_chain(paths)
+            .unwrap_or_else(|err| panic!("Failed to load fonts: {}", err))
+    }
+
+    /// Adds `fonts` to the end of the chain.
+    pub fn with_fallbacks(mut self, fonts: impl IntoIterator<Item = FontRenderer>) -> Self {
+        for mut font in fonts {
+            let rest = std::mem::take(&mut font.fallbacks);
+            self.fallbacks.push(font);
+            self.fallbacks.extend(rest);
+        }
+        self
+    }
+
+    /// The font of the chain that draws `c`: the first one having it.
+    /// Characters none of them have get the first font's missing glyph box.
+    fn font_for(&self, c: char) -> &FontRenderer {
+        std::iter::once(self)
+            .chain(&self.fallbacks)
+            .find(|renderer| renderer.font.lookup_glyph_index(c) != 0)
+            .unwrap_or(self)
+    }
+
+    /// Draws with `coverage` deciding which partly covered pixels are
+    /// black. The default, `Dither::Threshold(127)`, keeps pixels more
+    /// than half covered. A higher threshold keeps thin strokes of small
+    /// text, and dithering (e.g. `Dither::Bayer4`) draws edges as a
+    /// pattern, which suits large text. Ordered patterns are aligned to
+    /// the screen; error diffusion is done per glyph, so each letter
+    /// always looks the same.
+    pub fn with_coverage(mut self, coverage: Dither) -> Self {
+        self.coverage = coverage;
+        self
+    }
+
+    /// Rasterizes `c` in the font of the chain that has it, or returns it
+    /// from the glyph cache.
+    pub fn glyph(&self, c: char, size: f32) -> Arc<Glyph> {
+        self.font_for(c).own_glyph(c, size)
+    }
+
+    fn own_glyph(&self, c: char, size: f32) -> Arc<Glyph> {
+        cached(&self.glyphs, (self.id, c, size.to_bits(), 0), || {
+            let (metrics, coverage) = self.font.rasterize(c, size);
+            Glyph { metrics, coverage }
+        })
+    }
+
+    /// `c` in this font moved right by `phase` steps of a pixel divided
+    /// by SUBPIXEL_STEPS.
+    fn shifted_glyph(&self, c: char, size: f32, phase: u8) -> Arc<Glyph> {
+        if phase == 0 {
+            return self.own_glyph(c, size);
+        }
+        cached(&self.glyphs, (self.id, c, size.to_bits(), phase), || shift(&self.own_glyph(c, size), phase))
+    }
+
+    /// The pixels `c` in this font sets with `coverage`, for the pen at
+    /// `pen` on the screen.
+    fn mask(&self, c: char, size: f32, phase: u8, coverage: Dither, pen: (i32, i32)) -> Arc<Mask> {
+        let period = pattern_size(coverage);
+        let anchor = (pen.0.rem_euclid(period), pen.1.rem_euclid(period));
+        let key = ((self.id, c, size.to_bits(), phase), coverage, (anchor.0 as u8, anchor.1 as u8));
+        cached(&self.masks, key, || {
+            let glyph = self.shifted_glyph(c, size, phase);
+            let Metrics { width, height, xmin, ymin, .. } = glyph.metrics;
+            let (left, top) = (xmin, -ymin - height as i32);
+            // The dithering works on brightness, where coverage is darkness
+            let gray: Vec<u8> = glyph.coverage.iter().map(|coverage| 255 - coverage).collect();
+            let origin = ((anchor.0 + left).rem_euclid(period) as usize, (anchor.1 + top).rem_euclid(period) as usize);
+            let image = coverage.apply_to_image_at(&gray, width, height, origin);
+            Mask { left, top, image }
+        })
+    }
+
+    /// The kerning between two characters, in pixels at `size`. Characters
+    /// from different fonts of the chain aren't kerned.
+    pub fn kern(&self, left: char, right: char, size: f32) -> f32 {
+        let font = self.font_for(right);
+        if !Arc::ptr_eq(&self.font_for(left).font, &font.font) {
+            return 0.0;
+        }
+        font.kerning.get(&font.font, left, right) as f32 * size / font.font.units_per_em()
+    }
+
+    /// Calls `place` with each character of `text`, the font drawing it
+    /// and its pen position relative to the start, and returns where the
+    /// pen ends up. Drawing and measuring both go through this, so they
+    /// always agree.
+    fn pen_positions(&self, text: &str, size: f32, mut place: impl FnMut(char, &FontRenderer, f32)) -> f32 {
+        let mut pen = 0.0;
+        let mut previous = None;
+        for c in text.chars() {
+            if let Some(left) = previous {
+                pen += self.kern(left, c, size);
+            }
+            let font = self.font_for(c);
+            place(c, font, pen);
+            pen += font.font.metrics(c, size).advance_width;
+            previous = Some(c);
+        }
+        pen
+    }
+
+    /// The pen position at each character of `text` and after the last,
+    /// so that any part of the text can be measured after one pass.
+    pub fn pen_offsets(&self, text: &str, size: f32) -> Vec<f32> {
+        let mut offsets = Vec::with_capacity(text.len() + 1);
+        let end = self.pen_positions(text, size, |_, _, pen| offsets.push(pen));
+        offsets.push(end);
+        offsets
     }
 
     /// Standard black text rendering
@@ -22,38 +339,64 @@ impl FontRenderer {
 
     /// Colored text rendering (allows White text for selected rows)
     pub fn draw_text_colored(&self, display: &mut SharpDisplay, text: &str, x: i32, y: i32, size: f32, color: Pixel, ctx: &Context) {
-        let mut x_cursor = x as f32;
-
-        for char in text.chars() {
-            let (metrics, bitmap) = self.font.rasterize(char, size);
-            
-            for row in 0..metrics.height {
-                for col in 0..metrics.width {
-                    let coverage = bitmap[row * metrics.width + col];
-                    
-                    if coverage > 128 {
-                        let px = (x_cursor + col as f32 + metrics.xmin as f32) as i32;
-                        let py = (y as f32 + row as f32 - metrics.ymin as f32 - metrics.height as f32) as i32;
-                        
-                        // Bounds check to ensure we don't crash if text goes off-screen
-                        if px >= 0 && px < 400 && py >= 0 && py < 240 {
-                            display.draw_pixel(px as usize, py as usize, color, ctx);
-                        }
-                    }
-                }
-            }
-            x_cursor += metrics.advance_width;
-        }
+        self.pen_positions(text, size, |char, font, pen| {
+            let steps = ((x as f32 + pen) * SUBPIXEL_STEPS as f32).round() as i32;
+            let (left, phase) = (steps.div_euclid(SUBPIXEL_STEPS as i32), steps.rem_euclid(SUBPIXEL_STEPS as i32));
+            let mask = font.mask(char, size, phase as u8, self.coverage, (left, y));
+            // Parts off the screen are clipped
+            display.draw_image_colored(left + mask.left, y + mask.top, &mask.image, color, ctx);
+        });
     }
+
+    /// The width of `text` as drawn: text drawn at `x + width` continues
+    /// it without overlapping.
     pub fn calculate_width(&self, text: &str, size: f32) -> i32 {
-        let mut total_width = 0.0;
-        
-        for c in text.chars() {
-            // fontdue uses metrics() to get character dimensions
-            let metrics = self.font.metrics(c, size);
-            total_width += metrics.advance_width;
-        }
+        self.pen_positions(text, size, |_, _, _| {}).round() as i32
+    }
+}
+
+/// Returns the cached value for `key`, or makes and caches it. Making
+/// doesn't hold the lock; another thread may make the same value
+/// meanwhile, which is harmless.
+fn cached<K: Hash + Eq, V>(cache: &Mutex<LruCache<K, Arc<V>>>, key: K, make: impl FnOnce() -> V) -> Arc<V> {
+    if let Some(value) = cache.lock().get(&key) {
+        return value.clone();
+    }
+    let value = Arc::new(make());
+    cache.lock().put(key, value.clone());
+    value
+}
 
-        total_width as i32
+/// How many pixels apart the pattern of `coverage` repeats.
+fn pattern_size(coverage: Dither) -> i32 {
+    match coverage {
+        Dither::Bayer4 => 4,
+        Dither::Bayer8 => 8,
+        Dither::Threshold(_) | Dither::FloydSteinberg | Dither::Atkinson => 1,
     }
-}
\ No newline at end of file
+}
+
+/// `glyph` moved right by `phase` subpixel steps, sharing each column's
+/// coverage with the column to its right. It gets one column wider.
+fn shift(glyph: &Glyph, phase: u8) -> Glyph {
+    let Metrics { width, height, .. } = glyph.metrics;
+    if width == 0 {
+        return Glyph { metrics: glyph.metrics, coverage: glyph.coverage.clone() };
+    }
+    let moved = phase as u32;
+    let stays = (SUBPIXEL_STEPS - phase) as u32;
+    let mut coverage = Vec::with_capacity((width + 1) * height);
+    for row in glyph.coverage.chunks(width) {
+        let mut carried = 0;
+        for &value in row {
+            let value = value as u32;
+            coverage.push(((value * stays + carried) / SUBPIXEL_STEPS as u32) as u8);
+            carried = value * moved;
+        }
+        coverage.push((carried / SUBPIXEL_STEPS as u32) as u8);
+    }
+    Glyph {
+        metrics: Metrics { width: width + 1, ..glyph.metrics },
+        coverage,
+    }
+}
@@ -0,0 +1,275 @@
+//! Breaking text into lines, and finding where a byte offset of the
+//! text ends up on screen and back.
+//!
+//! Lines break at newlines (hard breaks) and after spaces (soft
+//! breaks). Spaces at the end of a soft line belong to it but don't
+//! count towards its width, so they can't push a word onto the next
+//! line. A word wider than a line is split between characters.
+//!
+//! Positions are relative to the layout's top left corner, in pixels.
+//! The cursor is a byte offset into the text. An offset where a line
+//! wraps is shown at the start of the next line.
+//!
+//! A layout keeps only where its lines are, not the text, so that pages
+//! can keep it next to the text they edit. Methods that need the text
+//! take it again, and it must be the text the layout was made for.
+
+use crate::context::Context;
+use crate::display::SharpDisplay;
+use crate::ui::fonts::FontRenderer;
+use std::ops::Range;
+
+/// Where lines sit between the layout's left and right edges.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Align {
+    Left,
+    Center,
+    /// Widens the spaces of wrapped lines to fill the width. The last
+    /// line of a paragraph is left aligned.
+    Justify,
+}
+
+/// One line of a layout.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Line {
+    /// The line's bytes in the text, including trailing spaces but not
+    /// the newline ending it
+    pub range: Range<usize>,
+    /// Offset of the line from the left edge
+    pub x: i32,
+    /// Width of the line without its trailing spaces
+    pub width: i32,
+    /// Ends at a newline rather than wrapping
+    pub hard_break: bool,
+    /// Pixels added to each space between words when justified
+    spacing: f32,
+}
+
+/// Text broken into lines for one font, size and width.
+pub struct TextLayout {
+    font: FontRenderer,
+    size: f32,
+    line_height: i32,
+    lines: Vec<Line>,
+}
+
+impl TextLayout {
+    /// Lays out `text` in lines at most `width` pixels wide, spaced
+    /// 1.2 times the font size.
+    pub fn new(text: &str, font: &FontRenderer, size: f32, width: i32, align: Align) -> Self {
+        let mut layout = Self {
+            font: font.clone(),
+            size,
+            line_height: (size * 1.2) as i32,
+            lines: Vec::new(),
+        };
+        let mut start = 0;
+        for paragraph in text.split('\n') {
+            layout.break_paragraph(text, start, width);
+            start += paragraph.len() + 1;
+        }
+        if let Some(last) = layout.lines.last_mut() {
+            last.hard_break = false;
+        }
+        for line in &mut layout.lines {
+            line.x = match align {
+                Align::Center => (width - line.width) / 2,
+                Align::Left | Align::Justify => 0,
+            };
+        }
+        if align == Align::Justify {
+            layout.justify(text, width);
+        }
+        layout
+    }
+
+    /// Spaces lines `line_height` pixels apart.
+    pub fn with_line_height(mut self, line_height: i32) -> Self {
+        self.line_height = line_height;
+        self
+    }
+
+    pub fn lines(&self) -> &[Line] {
+        &self.lines
+    }
+
+    pub fn line_height(&self) -> i32 {
+        self.line_height
+    }
+
+    /// Height of all lines together.
+    pub fn height(&self) -> i32 {
+        self.lines.len() as i32 * self.line_height
+    }
+
+    /// The text of line `index` in `text`.
+    pub fn line_text<'t>(&self, text: &'t str, index: usize) -> &'t str {
+        &text[self.lines[index].range.clone()]
+    }
+
+    /// Adds the lines of the paragraph of `text` starting at `start`.
+    /// The paragraph is measured once; lines are then found by
+    /// comparing the pen positions at its characters.
+    fn break_paragraph(&mut self, text: &str, start: usize, width: i32) {
+        let paragraph = text[start..].split('\n').next().unwrap_or_default();
+        let chars: Vec<(usize, char)> = paragraph
+            .char_indices()
+            .map(|(i, c)| (start + i, c))
+            .chain([(start + paragraph.len(), '\n')])
+            .collect();
+        let pens = self.font.pen_offsets(paragraph, self.size);
+        let end = chars.len() - 1;
+        let is_space = |i: usize| chars[i].1 == ' ';
+        // Width of the characters in `from..to`, without trailing spaces
+        let measure = |from: usize, mut to: usize| {
+            while to > from && is_space(to - 1) {
+                to -= 1;
+            }
+            (pens[to] - pens[from]).round() as i32
+        };
+
+        let (mut line, mut fits) = (0, 0);
+        loop {
+            // The end of the next word and the spaces after it
+            let mut next = fits;
+            while next < end && !is_space(next) {
+                next += 1;
+            }
+            while next < end && is_space(next) {
+                next += 1;
+            }
+            if next == end && measure(line, end) <= width {
+                self.push_line(chars[line].0..chars[end].0, measure(line, end), true);
+                return;
+            }
+            if measure(line, next) <= width {
+                fits = next;
+                continue;
+            }
+            if fits == line {
+                // Not even one word fits: split it after as many
+                // characters as fit, but at least one
+                fits = (line + 2..next).take_while(|&split| measure(line, split) <= width).last().unwrap_or(line + 1);
+            }
+            let hard_break = fits == end;
+            self.push_line(chars[line].0..chars[fits].0, measure(line, fits), hard_break);
+            if hard_break {
+                return;
+            }
+            line = fits;
+        }
+    }
+
+    fn push_line(&mut self, range: Range<usize>, width: i32, hard_break: bool) {
+        self.lines.push(Line { range, x: 0, width, hard_break, spacing: 0.0 });
+    }
+
+    /// Spreads the free space of each wrapped line over its spaces.
+    fn justify(&mut self, text: &str, width: i32) {
+        for line in &mut self.lines {
+            let words = text[line.range.clone()].trim_end_matches(' ');
+            let spaces = words.matches(' ').count();
+            if !line.hard_break && line.range.end < text.len() && spaces > 0 {
+                line.spacing = (width - line.width) as f32 / spaces as f32;
+                line.width = width;
+            }
+        }
+    }
+
+    /// The line `cursor` is shown on.
+    pub fn line_of(&self, cursor: usize) -> usize {
+        let last = self.lines.len().saturating_sub(1);
+        self.lines
+            .iter()
+            .position(|line| cursor < line.range.end || (cursor == line.range.end && line.hard_break))
+            .unwrap_or(last)
+    }
+
+    /// Where `cursor` in `text` is drawn: its x and the top of its line.
+    pub fn position(&self, text: &str, cursor: usize) -> (i32, i32) {
+        let index = self.line_of(cursor);
+        let Some(line) = self.lines.get(index) else { return (0, 0) };
+        let cursor = cursor.clamp(line.range.start, line.range.end);
+        (self.x_in_line(text, line, cursor), index as i32 * self.line_height)
+    }
+
+    /// The cursor in `text` closest to the point (`x`, `y`).
+    pub fn cursor_at(&self, text: &str, x: i32, y: i32) -> usize {
+        let index = (y / self.line_height.max(1)).max(0) as usize;
+        self.cursor_in_line(text, index.min(self.lines.len().saturating_sub(1)), x)
+    }
+
+    /// The cursor in `text` on line `index` closest to `x`.
+    pub fn cursor_in_line(&self, text: &str, index: usize, x: i32) -> usize {
+        let Some(line) = self.lines.get(index) else { return text.len() };
+        let line_text = &text[line.range.clone()];
+        let words = line_text.trim_end_matches(' ').len();
+        let pens = self.font.pen_offsets(line_text, self.size);
+        let mut spaces = 0;
+        let mut offsets: Vec<(usize, i32)> = Vec::new();
+        for ((i, c), pen) in line_text.char_indices().zip(&pens) {
+            offsets.push((line.range.start + i, self.x_at(line, *pen, spaces)));
+            if c == ' ' && i < words {
+                spaces += 1;
+            }
+        }
+        // The end of a wrapped line is the start of the next one
+        if line.hard_break || index + 1 == self.lines.len() || offsets.is_empty() {
+            let end = pens.last().copied().unwrap_or_default();
+            offsets.push((line.range.end, self.x_at(line, end, spaces)));
+        }
+        offsets
+            .into_iter()
+            .min_by_key(|&(_, cursor_x)| (cursor_x - x).abs())
+            .map_or(line.range.start, |(cursor, _)| cursor)
+    }
+
+    /// The x of `cursor`, which must be in `line`.
+    fn x_in_line(&self, text: &str, line: &Line, cursor: usize) -> i32 {
+        let line_text = &text[line.range.clone()];
+        let before = &line_text[..cursor - line.range.start];
+        // Only spaces between words are widened, not trailing ones
+        let words = line_text.trim_end_matches(' ').len();
+        let spaces = before[..before.len().min(words)].matches(' ').count();
+        // The pen at the character after the cursor includes its kern
+        // with the one before, as when the line is drawn
+        let pens = self.font.pen_offsets(line_text, self.size);
+        self.x_at(line, pens[before.chars().count()], spaces)
+    }
+
+    /// The x of a pen position in `line`, after `spaces` widened spaces.
+    fn x_at(&self, line: &Line, pen: f32, spaces: usize) -> i32 {
+        line.x + pen.round() as i32 + (line.spacing * spaces as f32) as i32
+    }
+
+    /// Draws all lines of `text` in black, with the top of the first at
+    /// (`x`, `y`).
+    pub fn draw(&self, text: &str, display: &mut SharpDisplay, x: i32, y: i32, ctx: &Context) {
+        self.draw_lines(text, display, 0..self.lines.len(), x, y, ctx);
+    }
+
+    /// Draws the lines of `text` in `lines` in black, the first of them
+    /// with its top at (`x`, `y`), e.g. to show the visible part of a
+    /// scrolled text.
+    pub fn draw_lines(&self, text: &str, display: &mut SharpDisplay, lines: Range<usize>, x: i32, y: i32, ctx: &Context) {
+        let baseline = y + self.size as i32;
+        for (row, index) in lines.enumerate() {
+            let Some(line) = self.lines.get(index) else { break };
+            let line_y = baseline + row as i32 * self.line_height;
+            if line.spacing == 0.0 {
+                self.font.draw_text(display, self.line_text(text, index), x + line.x, line_y, self.size, ctx);
+                continue;
+            }
+            // Justified lines are drawn word by word, each after one
+            // more widened space
+            let line_text = self.line_text(text, index);
+            let pens = self.font.pen_offsets(line_text, self.size);
+            let mut chars = 0;
+            for (spaces, word) in line_text.split_inclusive(' ').enumerate() {
+                let word_x = x + self.x_at(line, pens[chars], spaces);
+                self.font.draw_text(display, word, word_x, line_y, self.size, ctx);
+                chars += word.chars().count();
+            }
+        }
+    }
+}
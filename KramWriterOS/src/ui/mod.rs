@@ -1,2 +1,3 @@
 pub mod bitmap;
-pub mod fonts;
\ No newline at end of file
+pub mod fonts;
+pub mod layout;
\ No newline at end of file
@@ -0,0 +1,87 @@
+//! Drawing on the SharpDisplay without any assets.
+
+mod harness;
+
+use harness::{assert_display_snapshot, headless};
+use kramwriter_os::context::Context;
+use kramwriter_os::display::SharpDisplay;
+use kramwriter_os::ui::bitmap::Bitmap;
+use rpi_memory_display::{Image, Pixel};
+
+fn black_bounds(display: &SharpDisplay) -> Option<(usize, usize, usize, usize)> {
+    let buffer = display.buffer();
+    let mut bounds: Option<(usize, usize, usize, usize)> = None;
+    for y in 0..buffer.height() {
+        for x in 0..buffer.width() {
+            if buffer.get_pixel(x, y) == Pixel::Black {
+                let (x0, y0, x1, y1) = bounds.unwrap_or((x, y, x, y));
+                bounds = Some((x0.min(x), y0.min(y), x1.max(x), y1.max(y)));
+            }
+        }
+    }
+    bounds
+}
+
+#[test]
+fn built_in_font() {
+    let ctx = Context::default();
+    let mut display = headless();
+    display.clear(&ctx);
+    display.draw_text(10, 10, "LOGO MISSING", &ctx);
+    display.draw_text(10, 30, "Grüße aus Köln: ÄÖÜ äöü ß ½ © €", &ctx);
+    display.draw_text(10, 50, "0123456789 !\"#$%&'()*+,-./:;<=>?@[\\]^_`{|}~", &ctx);
+    assert_display_snapshot(&display, "built_in_font");
+}
+
+#[test]
+fn text_stays_in_its_cells() {
+    let ctx = Context::default();
+    let mut display = headless();
+    display.clear(&ctx);
+    display.draw_text(100, 50, "Wg", &ctx);
+    let (x0, y0, x1, y1) = black_bounds(&display).unwrap();
+    assert!(x0 >= 100 && x1 < 112, "{}..{}", x0, x1);
+    assert!(y0 >= 50 && y1 < 60, "{}..{}", y0, y1);
+
+    // Off the edges is clipped
+    display.draw_text(396, 236, "Wg", &ctx);
+}
+
+#[test]
+fn built_in_font_in_dark_mode() {
+    let light = Context::default();
+    let dark = Context { dark_mode: true, ..Context::default() };
+    let mut a = headless();
+    a.clear(&light);
+    a.draw_text(10, 10, "Dark", &light);
+    let mut b = headless();
+    b.clear(&dark);
+    b.draw_text(10, 10, "Dark", &dark);
+
+    let (a, b) = (a.buffer(), b.buffer());
+    for y in 0..a.height() {
+        for x in 0..a.width() {
+            assert_ne!(a.get_pixel(x, y), b.get_pixel(x, y), "({}, {})", x, y);
+        }
+    }
+}
+
+#[test]
+fn dark_bitmaps_leave_masked_out_pixels_alone() {
+    let dark = Context { dark_mode: true, ..Context::default() };
+    let mut display = headless();
+    display.clear(&dark);
+    // A 16x2 bitmap: black left half, white right half, with only the
+    // first row opaque
+    let image = Image::from_bytes(16, 2, vec![0x00, 0xff, 0x00, 0xff]).with_mask(vec![0xff, 0xff, 0x00, 0x00]);
+    let pixels = (0..32).map(|i| if i % 16 < 8 { Pixel::Black } else { Pixel::White }).collect();
+    let bmp = Bitmap { width: 16, height: 2, pixels, image };
+    display.draw_bitmap(4, 4, &bmp, &dark);
+
+    let buffer = display.buffer();
+    assert_eq!(buffer.get_pixel(4, 4), Pixel::White);
+    assert_eq!(buffer.get_pixel(12, 4), Pixel::Black);
+    // The masked out row keeps the dark background
+    assert_eq!(buffer.get_pixel(4, 5), Pixel::Black);
+    assert_eq!(buffer.get_pixel(12, 5), Pixel::Black);
+}
@@ -0,0 +1,176 @@
+//! Loading fonts, caching their glyphs and drawing with them.
+
+mod harness;
+
+use harness::headless;
+use kramwriter_os::context::Context;
+use kramwriter_os::display::SharpDisplay;
+use kramwriter_os::ui::fonts::{FontRegistry, FontRenderer};
+use rpi_memory_display::dither::Dither;
+use rpi_memory_display::Pixel;
+use std::sync::Arc;
+
+const INTER: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../fonts/Inter_28pt-Medium.ttf");
+const BEBAS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../fonts/BebasNeue-Regular.ttf");
+
+#[test]
+fn fonts_are_loaded_once() {
+    let registry = FontRegistry::new(16);
+    let a = registry.load(INTER).unwrap();
+    let b = registry.load(INTER).unwrap();
+    assert!(Arc::ptr_eq(&a.font, &b.font));
+
+    let bebas = registry.load(BEBAS).unwrap();
+    assert!(!Arc::ptr_eq(&a.font, &bebas.font));
+    assert!(registry.load("no/such/font.ttf").is_err());
+}
+
+#[test]
+fn glyphs_are_cached_per_font_and_size() {
+    let registry = FontRegistry::new(16);
+    let inter = registry.load(INTER).unwrap();
+    let bebas = registry.load(BEBAS).unwrap();
+
+    let glyph = inter.glyph('a', 22.0);
+    assert!(Arc::ptr_eq(&glyph, &inter.glyph('a', 22.0)));
+    assert!(Arc::ptr_eq(&glyph, &registry.load(INTER).unwrap().glyph('a', 22.0)));
+    assert!(!Arc::ptr_eq(&glyph, &inter.glyph('a', 18.0)));
+    assert!(!Arc::ptr_eq(&glyph, &bebas.glyph('a', 22.0)));
+    assert_eq!(registry.cached_glyphs(), 3);
+
+    let (metrics, coverage) = inter.font.rasterize('a', 22.0);
+    assert_eq!(glyph.metrics, metrics);
+    assert_eq!(glyph.coverage, coverage);
+}
+
+#[test]
+fn least_recently_used_glyphs_are_dropped() {
+    let registry = FontRegistry::new(3);
+    let font = registry.load(INTER).unwrap();
+    let a = font.glyph('a', 22.0);
+    let b = font.glyph('b', 22.0);
+    font.glyph('c', 22.0);
+    font.glyph('a', 22.0);
+    font.glyph('d', 22.0);
+
+    assert_eq!(registry.cached_glyphs(), 3);
+    assert!(Arc::ptr_eq(&a, &font.glyph('a', 22.0)));
+    assert!(!Arc::ptr_eq(&b, &font.glyph('b', 22.0)));
+}
+
+/// The black pixels of `text` drawn at (10, 40) in 22px.
+fn drawn(font: &FontRenderer, text: &str) -> Vec<(usize, usize)> {
+    drawn_at(font, text, 10, 40, 22.0)
+}
+
+fn drawn_at(font: &FontRenderer, text: &str, x: i32, y: i32, size: f32) -> Vec<(usize, usize)> {
+    let ctx = Context::default();
+    let mut display = headless();
+    display.clear(&ctx);
+    font.draw_text(&mut display, text, x, y, size, &ctx);
+    black(&display)
+}
+
+fn black(display: &SharpDisplay) -> Vec<(usize, usize)> {
+    let buffer = display.buffer();
+    (0..buffer.height())
+        .flat_map(|y| (0..buffer.width()).map(move |x| (x, y)))
+        .filter(|&(x, y)| buffer.get_pixel(x, y) == Pixel::Black)
+        .collect()
+}
+
+#[test]
+fn pairs_are_kerned() {
+    let registry = FontRegistry::new(64);
+    for path in [INTER, BEBAS] {
+        let font = registry.load(path).unwrap();
+        assert!(font.kern('A', 'V', 40.0) < -1.0, "{}", path);
+        assert!(font.kern('T', 'O', 40.0) < 0.0, "{}", path);
+        assert_eq!(font.kern('A', 'V', 80.0), font.kern('A', 'V', 40.0) * 2.0);
+
+        let apart = font.calculate_width("A", 40.0) + font.calculate_width("V", 40.0);
+        assert!(font.calculate_width("AV", 40.0) < apart);
+    }
+}
+
+#[test]
+fn width_matches_drawn_text() {
+    let registry = FontRegistry::new(256);
+    let inter = registry.load(INTER).unwrap();
+    for text in ["AVATAR", "Tokyo, Wavy tilt", "illicit little lilies"] {
+        let width = inter.calculate_width(text, 22.0) as usize;
+        let right = drawn(&inter, text).iter().map(|&(x, _)| x).max().unwrap();
+        // The last glyph ends within its advance, give or take its side bearing
+        assert!(right < 10 + width + 2 && right + 4 > 10 + width, "{}: {} vs {}", text, right, 10 + width);
+    }
+}
+
+#[test]
+fn coverage_modes() {
+    let registry = FontRegistry::new(256);
+    let inter = registry.load(INTER).unwrap();
+    let text = "Thin strokes";
+    let half = drawn(&inter, text);
+    let bold = drawn(&inter.clone().with_coverage(Dither::Threshold(223)), text);
+    let thin = drawn(&inter.clone().with_coverage(Dither::Threshold(31)), text);
+    let dithered = drawn(&inter.clone().with_coverage(Dither::Bayer4), text);
+
+    assert!(thin.len() < half.len() && half.len() < bold.len());
+    assert!(thin.iter().all(|pixel| half.contains(pixel)));
+    assert!(half.iter().all(|pixel| bold.contains(pixel)));
+    assert_ne!(dithered, half);
+    // Renderers keep their own mode while sharing the font
+    assert_eq!(drawn(&registry.load(INTER).unwrap(), text), half);
+}
+
+#[test]
+fn missing_characters_come_from_fallback_fonts() {
+    let registry = FontRegistry::new(64);
+    let inter = registry.load(INTER).unwrap();
+    let bebas = registry.load(BEBAS).unwrap();
+    // Bebas Neue has no Greek or Cyrillic; fallbacks that fail to load are left out
+    let chain = registry.load_chain(&[BEBAS, "no/such/font.ttf", INTER]).unwrap();
+
+    assert!(Arc::ptr_eq(&chain.glyph('A', 22.0), &bebas.glyph('A', 22.0)));
+    assert!(Arc::ptr_eq(&chain.glyph('λ', 22.0), &inter.glyph('λ', 22.0)));
+    // Characters none of the fonts have get the first one's box
+    assert!(Arc::ptr_eq(&chain.glyph('日', 22.0), &bebas.glyph('日', 22.0)));
+
+    assert_eq!(chain.calculate_width("λЯ", 22.0), inter.calculate_width("λЯ", 22.0));
+    assert_eq!(drawn(&chain, "λЯ"), drawn(&inter, "λЯ"));
+    assert_eq!(chain.kern('A', 'Я', 40.0), 0.0);
+    let mixed = bebas.font.metrics('A', 22.0).advance_width + inter.font.metrics('Я', 22.0).advance_width;
+    assert_eq!(chain.calculate_width("AЯ", 22.0), mixed.round() as i32);
+
+    assert!(registry.load_chain(&["no/such/font.ttf", INTER]).is_err());
+}
+
+#[test]
+fn drawn_glyphs_are_cached() {
+    let registry = FontRegistry::new(64);
+    let inter = registry.load(INTER).unwrap();
+    let first = drawn(&inter, "cached");
+    let masks = registry.cached_masks();
+    assert_eq!(masks, 5);
+    assert_eq!(drawn(&inter, "cached"), first);
+    assert_eq!(registry.cached_masks(), masks);
+
+    drawn(&inter.clone().with_coverage(Dither::Atkinson), "cached");
+    assert_eq!(registry.cached_masks(), masks * 2);
+}
+
+#[test]
+fn ordered_dithering_is_aligned_to_the_screen() {
+    let registry = FontRegistry::new(256);
+    let dithered = registry.load(INTER).unwrap().with_coverage(Dither::Bayer4);
+    let moved = |pixels: Vec<(usize, usize)>, dx: usize, dy: usize| -> Vec<(usize, usize)> {
+        pixels.into_iter().map(|(x, y)| (x - dx, y - dy)).collect()
+    };
+    let text = "Bold";
+    let here = drawn_at(&dithered, text, 10, 60, 60.0);
+    // A whole pattern away the text looks the same...
+    assert_eq!(moved(drawn_at(&dithered, text, 14, 68, 60.0), 4, 8), here);
+    // ...but a pixel away the pattern stays put while the text moves
+    assert_ne!(moved(drawn_at(&dithered, text, 11, 60, 60.0), 1, 0), here);
+    assert_ne!(moved(drawn_at(&dithered, text, 10, 61, 60.0), 0, 1), here);
+}
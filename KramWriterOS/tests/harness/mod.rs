@@ -0,0 +1,177 @@
+//! Drives an App with a headless display and a script of keys, and
+//! compares the screen with 1-bit snapshots in `tests/snapshots`.
+//!
+//! The app uses this checkout's assets and fonts. Its documents folder
+//! starts out holding an empty `drafts` folder, and is shared by all
+//! tests in a test binary.
+//!
+//! Run the tests with `UPDATE_SNAPSHOTS=1` to (re)write the snapshots
+//! after an intended change to how a page looks, and check the new
+//! PBM files before committing them.
+
+// Each test binary uses its own part of the harness
+#![allow(dead_code)]
+
+use kramwriter_os::app::{App, KeySource};
+use kramwriter_os::context::Context;
+use kramwriter_os::display::{Headless, SharpDisplay};
+use kramwriter_os::paths::{self, paths, Paths};
+use rpi_memory_display::{MemoryDisplayBuffer, Pixel};
+use std::collections::VecDeque;
+use std::env;
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::RecvTimeoutError;
+use std::sync::Once;
+use std::time::Duration;
+use termion::event::Key;
+
+/// One step of a script.
+#[derive(Debug, Clone, Copy)]
+pub enum Step {
+    Key(Key),
+    /// Lets the top page tick once without a key press.
+    Tick,
+}
+
+/// Hands out the steps of a script, then disconnects so that
+/// `App::run` returns with the last frame still on screen.
+pub struct Script(VecDeque<Step>);
+
+impl KeySource for Script {
+    fn next_key(&mut self, _timeout: Duration) -> Result<Key, RecvTimeoutError> {
+        match self.0.pop_front() {
+            Some(Step::Key(key)) => Ok(key),
+            Some(Step::Tick) => Err(RecvTimeoutError::Timeout),
+            None => Err(RecvTimeoutError::Disconnected),
+        }
+    }
+}
+
+/// Shorthand for a step pressing `key`.
+pub fn key(key: Key) -> Step {
+    Step::Key(key)
+}
+
+/// Shorthand for steps typing `text`.
+pub fn typing(text: &str) -> impl Iterator<Item = Step> + '_ {
+    text.chars().map(|c| Step::Key(Key::Char(c)))
+}
+
+/// Starts the app on the logo page with default settings, which
+/// aren't saved, and replays `steps`.
+pub fn run(steps: impl IntoIterator<Item = Step>) -> App {
+    replay(App::with_context(headless(), Context::default()), steps)
+}
+
+/// Replays `steps` on `app`.
+pub fn replay(mut app: App, steps: impl IntoIterator<Item = Step>) -> App {
+    let mut script = Script(steps.into_iter().collect());
+    app.run(&mut script).expect("app failed");
+    app
+}
+
+/// A display that draws into its buffer only. Sets up the paths,
+/// which pages use as soon as they are created.
+pub fn headless() -> SharpDisplay {
+    init_paths();
+    SharpDisplay::new(Box::new(Headless))
+}
+
+/// A scratch file or directory for one test.
+pub fn scratch(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name)
+}
+
+/// The app's documents folder.
+pub fn documents() -> &'static Path {
+    init_paths();
+    &paths().documents
+}
+
+/// Points the app at this checkout and a scratch documents folder.
+/// Must run before anything uses the paths.
+pub fn init_paths() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        let checkout = Path::new(env!("CARGO_MANIFEST_DIR")).parent().unwrap();
+        let scratch = scratch("pages");
+        let _ = fs::remove_dir_all(&scratch);
+        let documents = scratch.join("documents");
+        fs::create_dir_all(documents.join("drafts")).unwrap();
+        paths::set(Paths::for_checkout(checkout, documents, scratch.join("data")))
+            .expect("paths were used before the harness set them");
+    });
+}
+
+/// Compares the app's screen with `tests/snapshots/<name>.pbm`.
+pub fn assert_snapshot(app: &App, name: &str) {
+    assert_snapshot_except(app, name, &[]);
+}
+
+/// Like `assert_snapshot`, but `ignored` regions (`x, y, width,
+/// height`) are blanked first, e.g. for the clock.
+pub fn assert_snapshot_except(app: &App, name: &str, ignored: &[(usize, usize, usize, usize)]) {
+    assert_display_snapshot_except(app.display(), name, ignored);
+}
+
+/// Like `assert_snapshot`, for a display drawn on directly.
+pub fn assert_display_snapshot(display: &SharpDisplay, name: &str) {
+    assert_display_snapshot_except(display, name, &[]);
+}
+
+fn assert_display_snapshot_except(display: &SharpDisplay, name: &str, ignored: &[(usize, usize, usize, usize)]) {
+    let mut actual = display.buffer().clone();
+    for &(x, y, width, height) in ignored {
+        for y in y..y + height {
+            for x in x..x + width {
+                actual.set_pixel(x, y, Pixel::White);
+            }
+        }
+    }
+    let actual = &actual;
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/snapshots")
+        .join(format!("{}.pbm", name));
+
+    if env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        actual.write_pbm(BufWriter::new(File::create(&path).unwrap())).unwrap();
+        return;
+    }
+
+    let file = File::open(&path)
+        .unwrap_or_else(|_| panic!("no snapshot {}, run with UPDATE_SNAPSHOTS=1", path.display()));
+    let expected = MemoryDisplayBuffer::read_pbm(BufReader::new(file)).unwrap();
+    if expected.width() != actual.width() || expected.height() != actual.height() {
+        panic!("snapshot {} is {}x{}, screen is {}x{}",
+            name, expected.width(), expected.height(), actual.width(), actual.height());
+    }
+    let differing = differing_pixels(&expected, actual);
+    if differing > 0 {
+        let saved = scratch(&format!("{}.pbm", name));
+        actual.write_pbm(BufWriter::new(File::create(&saved).unwrap())).unwrap();
+        panic!("{} pixels differ from snapshot {}, screen saved to {}", differing, name, saved.display());
+    }
+}
+
+fn differing_pixels(expected: &MemoryDisplayBuffer, actual: &MemoryDisplayBuffer) -> usize {
+    let mut count = 0;
+    for y in 0..actual.height() {
+        for x in 0..actual.width() {
+            if expected.get_pixel(x, y) != actual.get_pixel(x, y) {
+                count += 1;
+            }
+        }
+    }
+    count
+}
+
+/// Number of black pixels on screen, for quick sanity checks.
+pub fn black_pixels(app: &App) -> usize {
+    let buffer = app.display().buffer();
+    (0..buffer.height())
+        .flat_map(|y| (0..buffer.width()).map(move |x| (x, y)))
+        .filter(|&(x, y)| buffer.get_pixel(x, y) == Pixel::Black)
+        .count()
+}
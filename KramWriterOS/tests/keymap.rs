@@ -0,0 +1,86 @@
+//! Translating keys for the keyboard layout.
+
+mod harness;
+
+use harness::{black_pixels, headless, init_paths, key, replay, typing};
+use kramwriter_os::app::App;
+use kramwriter_os::context::{Context, KeyboardLayout};
+use kramwriter_os::keymap::Keymap;
+use kramwriter_os::paths::paths;
+use std::fs;
+use termion::event::Key;
+
+fn typed(keymap: &Keymap, text: &str) -> String {
+    text.chars()
+        .map(|c| match keymap.translate(Key::Char(c)) {
+            Key::Char(c) => c,
+            key => panic!("{:?} became {:?}", c, key),
+        })
+        .collect()
+}
+
+#[test]
+fn qwerty_is_unchanged() {
+    init_paths();
+    let keymap = Keymap::for_layout(&KeyboardLayout::Qwerty).unwrap();
+    assert_eq!(typed(&keymap, "Why, zebra?"), "Why, zebra?");
+}
+
+#[test]
+fn built_in_layouts() {
+    init_paths();
+    let qwertz = Keymap::for_layout(&KeyboardLayout::Qwertz).unwrap();
+    assert_eq!(typed(&qwertz, "Zy;'[-"), "Yzöäüß");
+    assert_eq!(qwertz.translate(Key::Ctrl('y')), Key::Ctrl('z'));
+    assert_eq!(qwertz.translate(Key::Up), Key::Up);
+
+    let azerty = Keymap::for_layout(&KeyboardLayout::Azerty).unwrap();
+    assert_eq!(typed(&azerty, "qwa;1!"), "azqm&1");
+
+    let dvorak = Keymap::for_layout(&KeyboardLayout::Dvorak).unwrap();
+    assert_eq!(typed(&dvorak, "jdpps"), "hello");
+    // Shortcuts on keys without a letter stay as they are
+    assert_eq!(dvorak.translate(Key::Ctrl('q')), Key::Ctrl('q'));
+
+    let colemak = Keymap::for_layout(&KeyboardLayout::Colemak).unwrap();
+    assert_eq!(typed(&colemak, "hkuu;"), "hello");
+}
+
+#[test]
+fn keymap_files() {
+    let neo = KeyboardLayout::Custom(String::from("neo"));
+    let keymap = Keymap::parse("# comment\n\nabc  xyz\nA X\n", neo.clone()).unwrap();
+    assert_eq!(keymap.layout(), &neo);
+    assert_eq!(typed(&keymap, "abcdA"), "xyzdX");
+
+    let err = Keymap::parse("abc xy\n", neo.clone()).unwrap_err();
+    assert_eq!(err.to_string(), "line 1: columns have different lengths");
+    let err = Keymap::parse("# ok\nabc\n", neo).unwrap_err();
+    assert_eq!(err.to_string(), "line 2: expected two columns, found 1");
+}
+
+#[test]
+fn broken_keymap_files_leave_keys_untranslated() {
+    init_paths();
+    fs::create_dir_all(paths().keymaps()).unwrap();
+    fs::write(paths().keymaps().join("broken.keymap"), "abc xy\n").unwrap();
+    let broken = KeyboardLayout::Custom(String::from("broken"));
+    let err = Keymap::for_layout(&broken).unwrap_err();
+    assert!(format!("{:#}", err).ends_with("line 1: columns have different lengths"));
+
+    // The app still runs, typing what the terminal sends
+    let ctx = Context { layout: broken, ..Context::default() };
+    let app = replay(App::with_context(headless(), ctx), typing("abc"));
+    assert_eq!(app.context().layout.name(), "broken");
+}
+
+#[test]
+fn app_translates_shortcuts() {
+    // On Dvorak, X is where a US keyboard has B, so Ctrl-b exits
+    let ctx = Context { layout: KeyboardLayout::Dvorak, ..Context::default() };
+    let app = replay(App::with_context(headless(), ctx), [key(Key::Ctrl('b'))]);
+    assert_eq!(black_pixels(&app), 0);
+
+    let app = replay(App::with_context(headless(), Context::default()), [key(Key::Ctrl('b'))]);
+    assert!(black_pixels(&app) > 0);
+}
@@ -0,0 +1,144 @@
+//! Breaking text into lines and hit-testing it.
+
+use kramwriter_os::ui::fonts::{FontRegistry, FontRenderer};
+use kramwriter_os::ui::layout::{Align, TextLayout};
+
+const INTER: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../fonts/Inter_28pt-Medium.ttf");
+
+fn inter() -> FontRenderer {
+    FontRegistry::new(256).load(INTER).unwrap()
+}
+
+fn lines<'t>(layout: &TextLayout, text: &'t str) -> Vec<&'t str> {
+    (0..layout.lines().len()).map(|i| layout.line_text(text, i)).collect()
+}
+
+#[test]
+fn hard_and_soft_breaks() {
+    let text = "The quick brown fox jumps\n\nover the lazy dog";
+    let layout = TextLayout::new(text, &inter(), 22.0, 150, Align::Left);
+
+    assert_eq!(lines(&layout, text), ["The quick ", "brown fox ", "jumps", "", "over the lazy ", "dog"]);
+    let hard: Vec<bool> = layout.lines().iter().map(|line| line.hard_break).collect();
+    assert_eq!(hard, [false, false, true, true, false, false]);
+    for line in layout.lines() {
+        assert!(line.width <= 150);
+        assert_eq!(line.x, 0);
+    }
+    // Lines cover the whole text but the newlines
+    let ranges: Vec<_> = layout.lines().iter().map(|line| line.range.clone()).collect();
+    for pair in ranges.windows(2) {
+        let newline = usize::from(&text[pair[0].end..pair[1].start] == "\n");
+        assert_eq!(pair[1].start, pair[0].end + newline);
+    }
+    assert_eq!(ranges.last().unwrap().end, text.len());
+    assert_eq!(layout.height(), 6 * 26);
+}
+
+#[test]
+fn overlong_words_are_split() {
+    let text = "A Donaudampfschifffahrtsgesellschaft, 日本語";
+    let layout = TextLayout::new(text, &inter(), 22.0, 100, Align::Left);
+
+    assert!(layout.lines().len() > 3);
+    assert_eq!(lines(&layout, text).concat(), text);
+    assert_eq!(lines(&layout, text)[0], "A ");
+    for line in layout.lines() {
+        assert!(line.width <= 100 && !line.range.is_empty());
+    }
+    // A character wider than a line still gets a line of its own
+    let layout = TextLayout::new("WW", &inter(), 22.0, 5, Align::Left);
+    assert_eq!(lines(&layout, "WW"), ["W", "W"]);
+}
+
+#[test]
+fn alignment() {
+    let text = "Centered and justified lines of text\nend";
+    let font = inter();
+    let center = TextLayout::new(text, &font, 22.0, 200, Align::Center);
+    for line in center.lines() {
+        assert_eq!(line.x, (200 - line.width) / 2);
+    }
+
+    let justified = TextLayout::new(text, &font, 22.0, 200, Align::Justify);
+    let left = TextLayout::new(text, &font, 22.0, 200, Align::Left);
+    assert_eq!(justified.lines().len(), left.lines().len());
+    let last_wrapped = justified.lines().len() - 3;
+    for (i, line) in justified.lines().iter().enumerate() {
+        assert_eq!(line.x, 0);
+        if i <= last_wrapped {
+            assert_eq!(line.width, 200);
+        } else {
+            // Last lines of paragraphs keep their width
+            assert_eq!(line.width, left.lines()[i].width);
+        }
+    }
+    // The last word of a justified line ends at the right edge
+    let first = &justified.lines()[0];
+    let end = first.range.start + justified.line_text(text, 0).trim_end().len();
+    assert!((justified.position(text, end).0 - 200).abs() <= 2);
+}
+
+#[test]
+fn cursor_positions_round_trip() {
+    let text = "Tippen, wrap and\nnewlines: Grüße aus Köln\n\nlast";
+    let font = inter();
+    for align in [Align::Left, Align::Center, Align::Justify] {
+        let layout = TextLayout::new(text, &font, 22.0, 160, align);
+        let cursors = text.char_indices().map(|(i, _)| i).chain([text.len()]);
+        for cursor in cursors {
+            let (x, y) = layout.position(text, cursor);
+            assert_eq!(y, layout.line_of(cursor) as i32 * layout.line_height());
+            assert_eq!(layout.cursor_at(text, x, y + 5), cursor, "{:?} at {}", align, cursor);
+        }
+    }
+}
+
+#[test]
+fn cursors_at_line_ends() {
+    let text = "ab cd\nef";
+    let font = inter();
+    let width = font.calculate_width("ab", 22.0) + 2;
+    let layout = TextLayout::new(text, &font, 22.0, width, Align::Left);
+    assert_eq!(lines(&layout, text), ["ab ", "cd", "ef"]);
+
+    // Where a line wraps the cursor goes to the next one, but before a
+    // newline it stays
+    assert_eq!(layout.line_of(3), 1);
+    assert_eq!(layout.line_of(5), 1);
+    assert_eq!(layout.line_of(6), 2);
+    assert_eq!(layout.line_of(8), 2);
+    // A wrapped line can't be clicked behind its end
+    assert_eq!(layout.cursor_in_line(text, 0, 1000), 2);
+    assert_eq!(layout.cursor_in_line(text, 1, 1000), 5);
+    assert_eq!(layout.cursor_at(text, -10, -10), 0);
+    assert_eq!(layout.cursor_at(text, 1000, 1000), text.len());
+
+    let empty = TextLayout::new("", &font, 22.0, width, Align::Center);
+    assert_eq!(empty.lines().len(), 1);
+    assert_eq!(empty.position("", 0), (width / 2, 0));
+}
+
+#[test]
+fn line_widths_match_the_drawn_lines() {
+    let text = "Kerning, AVATAR und Ärger: ein Absatz mit vielen Wörtern\nund Zeilen";
+    let font = inter();
+    let layout = TextLayout::new(text, &font, 22.0, 180, Align::Left);
+    for (i, line) in layout.lines().iter().enumerate() {
+        let drawn = font.calculate_width(layout.line_text(text, i).trim_end(), 22.0);
+        assert!((line.width - drawn).abs() <= 1, "{:?}: {} vs {}", layout.line_text(text, i), line.width, drawn);
+    }
+}
+
+#[test]
+fn cursors_are_drawn_at_kerned_pen_positions() {
+    let text = "AVA WAVE";
+    let font = inter();
+    let pens = font.pen_offsets(text, 22.0);
+    // "AV" is kerned, so the V doesn't start where a lone "A" ends
+    assert_ne!(font.calculate_width("A", 22.0), pens[1].round() as i32);
+    let layout = TextLayout::new(text, &font, 22.0, 300, Align::Left);
+    for (i, (cursor, _)) in text.char_indices().enumerate() {
+        assert_eq!(layout.position(text, cursor).0, pens[i].round() as i32, "at {}", cursor);
+    }
+}
@@ -0,0 +1,143 @@
+//! Scripted runs through the page stack, checked against snapshots.
+
+mod harness;
+
+use harness::{assert_snapshot, assert_snapshot_except, black_pixels, documents, headless, key, replay, run, typing, Step};
+use kramwriter_os::app::App;
+use kramwriter_os::context::{Context, KeyboardLayout};
+use kramwriter_os::paths::paths;
+use std::fs;
+use std::time::Duration;
+use termion::event::Key;
+
+#[test]
+fn starts_on_the_logo() {
+    let app = run([]);
+    assert_snapshot(&app, "logo");
+}
+
+#[test]
+fn menu_escape_returns_to_the_logo() {
+    let app = run([key(Key::Char('\n')), key(Key::Down), key(Key::Esc)]);
+    assert_snapshot(&app, "logo");
+}
+
+#[test]
+fn ticks_alone_change_nothing() {
+    let app = run([Step::Tick, Step::Tick, Step::Tick]);
+    assert_snapshot(&app, "logo");
+}
+
+#[test]
+fn idle_display_sleeps_until_a_key() {
+    let app = App::with_context(headless(), Context::default()).with_idle_timeout(Duration::ZERO);
+    let app = replay(app, [Step::Tick, Step::Tick]);
+    assert!(app.display().is_asleep());
+
+    // The waking key only wakes, it doesn't open the menu
+    let app = replay(app, [key(Key::Char('\n'))]);
+    assert!(!app.display().is_asleep());
+    assert_snapshot(&app, "logo");
+}
+
+#[test]
+fn screenshots_are_saved_with_the_data() {
+    let app = run([key(Key::F(12))]);
+    assert_snapshot(&app, "logo");
+    let saved = fs::read_dir(paths().screenshots()).unwrap().count();
+    assert_eq!(saved, 1);
+}
+
+#[test]
+fn settings_toggle_dark_mode() {
+    // Logo -> menu -> settings -> dark mode
+    let mut steps = vec![key(Key::Char('\n'))];
+    steps.extend([key(Key::Down); 3]);
+    steps.push(key(Key::Char('\n')));
+    steps.extend([key(Key::Down); 2]);
+    steps.push(key(Key::Char('\n')));
+    let app = run(steps.clone());
+    assert!(app.context().dark_mode);
+    assert_snapshot(&app, "settings_dark");
+
+    // Toggling again brings back the light screen
+    steps.push(key(Key::Char('\n')));
+    let app = run(steps);
+    assert!(!app.context().dark_mode);
+    let buffer = app.display().buffer();
+    assert!(black_pixels(&app) < buffer.width() * buffer.height() / 2);
+}
+
+#[test]
+fn keyboard_layout_cycles() {
+    // Logo -> menu -> settings -> keyboard layout
+    let mut steps = vec![key(Key::Char('\n'))];
+    steps.extend([key(Key::Down); 3]);
+    steps.push(key(Key::Char('\n')));
+    steps.extend([key(Key::Down); 4]);
+    steps.push(key(Key::Char('\n')));
+    let app = run(steps.clone());
+    assert_eq!(app.context().layout, KeyboardLayout::Qwertz);
+    assert_snapshot(&app, "settings_keyboard");
+
+    // Layouts without art are drawn as text
+    steps.push(key(Key::Char('\n')));
+    let app = run(steps.clone());
+    assert_eq!(app.context().layout, KeyboardLayout::Azerty);
+    assert_snapshot(&app, "settings_keyboard_text");
+
+    steps.extend([key(Key::Char('\n')); 3]);
+    let app = run(steps);
+    assert_eq!(app.context().layout, KeyboardLayout::Qwerty);
+}
+
+#[test]
+fn choose_a_timezone() {
+    // Logo -> menu -> settings -> timezone, search and pick Kolkata
+    // (no DST, so the highlighted band doesn't change over the year)
+    let mut steps = vec![key(Key::Char('\n'))];
+    steps.extend([key(Key::Down); 3]);
+    steps.extend([key(Key::Char('\n')), key(Key::Char('\n'))]);
+    steps.extend(typing("kolk"));
+    let app = run(steps.clone());
+    assert_snapshot(&app, "timezone");
+
+    steps.push(key(Key::Char('\n')));
+    let app = run(steps);
+    assert_eq!(app.context().timezone, "Asia/Kolkata");
+}
+
+#[test]
+fn write_a_new_file() {
+    let mut steps = vec![
+        // Logo -> menu -> write -> new file -> browser
+        key(Key::Char('\n')),
+        key(Key::Char('\n')),
+        key(Key::Char('\n')),
+    ];
+    assert_snapshot(&run(steps.clone()), "browser");
+
+    steps.extend([
+        // Into drafts, then "new file" in the footer
+        key(Key::Char('\n')),
+        key(Key::Right),
+        key(Key::Right),
+        key(Key::Char('\n')),
+    ]);
+    steps.extend(typing("draft"));
+    steps.extend([
+        // Save the name, back to the list, open the new file
+        key(Key::Char('\n')),
+        key(Key::Char('\n')),
+        key(Key::Up),
+        key(Key::Down),
+        key(Key::Char('\n')),
+    ]);
+    steps.extend(typing("Hello"));
+    steps.push(key(Key::Ctrl('s')));
+    let app = run(steps);
+    // The status bar clock shows the real time
+    assert_snapshot_except(&app, "editor", &[(300, 218, 45, 22)]);
+    let saved = fs::read_to_string(documents().join("drafts/DRAFT.txt")).unwrap();
+    assert_eq!(saved, "Hello");
+}
@@ -0,0 +1,152 @@
+//! Saving and loading the Context.
+
+mod harness;
+
+use harness::{headless, key, replay, scratch};
+use kramwriter_os::app::App;
+use kramwriter_os::context::KeyboardLayout;
+use kramwriter_os::settings::{SettingsStore, VERSION};
+use serde_json::Value;
+use std::fs;
+use std::path::PathBuf;
+use termion::event::Key;
+
+/// A settings file path for one test, with no file there yet.
+fn settings_file(test: &str) -> PathBuf {
+    let dir = scratch("settings").join(test);
+    let _ = fs::remove_dir_all(&dir);
+    dir.join("settings.json")
+}
+
+fn read(path: &PathBuf) -> Value {
+    serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap()
+}
+
+#[test]
+fn missing_file_gives_defaults() {
+    let path = settings_file("missing");
+    let mut store = SettingsStore::new(&path);
+    let ctx = store.load();
+    assert!(!ctx.dark_mode);
+    assert_eq!(ctx.timezone, "UTC");
+    assert_eq!(ctx.layout, KeyboardLayout::Qwerty);
+
+    // Nothing is written until a setting changes
+    store.save(&ctx).unwrap();
+    assert!(!path.exists());
+}
+
+#[test]
+fn changes_are_saved_and_loaded() {
+    let path = settings_file("round_trip");
+    let mut store = SettingsStore::new(&path);
+    let mut ctx = store.load();
+    ctx.dark_mode = true;
+    ctx.layout = KeyboardLayout::Qwertz;
+    ctx.simplenote_email = Some(String::from("kram@example.com"));
+    store.save(&ctx).unwrap();
+
+    let saved = read(&path);
+    assert_eq!(saved["version"], VERSION);
+    assert_eq!(saved["layout"], "qwertz");
+    assert!(saved.get("status").is_none());
+
+    let ctx = SettingsStore::new(&path).load();
+    assert!(ctx.dark_mode);
+    assert_eq!(ctx.layout, KeyboardLayout::Qwertz);
+    assert_eq!(ctx.simplenote_email.as_deref(), Some("kram@example.com"));
+}
+
+#[test]
+fn missing_keys_get_defaults() {
+    let path = settings_file("missing_keys");
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(&path, r#"{ "version": 1, "dark_mode": true, "retired": 3 }"#).unwrap();
+    let ctx = SettingsStore::new(&path).load();
+    assert!(ctx.dark_mode);
+    assert_eq!(ctx.timezone, "UTC");
+    assert_eq!(ctx.simplenote_token, None);
+}
+
+#[test]
+fn unreadable_file_gives_defaults_and_is_kept() {
+    let path = settings_file("unreadable");
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(&path, "{ not json").unwrap();
+    let mut store = SettingsStore::new(&path);
+    let ctx = store.load();
+    assert!(!ctx.dark_mode);
+    store.save(&ctx).unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), "{ not json");
+}
+
+#[test]
+fn one_corrupt_key_keeps_the_others() {
+    let path = settings_file("corrupt_key");
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    let corrupt = format!(
+        r#"{{ "version": {}, "dark_mode": true, "layout": "nonsense", "timezone": 5, "simplenote_email": "kram@example.com" }}"#,
+        VERSION
+    );
+    fs::write(&path, &corrupt).unwrap();
+    let mut store = SettingsStore::new(&path);
+    let mut ctx = store.load();
+    assert!(ctx.dark_mode);
+    assert_eq!(ctx.layout, KeyboardLayout::Qwerty);
+    assert_eq!(ctx.timezone, "UTC");
+    assert_eq!(ctx.simplenote_email.as_deref(), Some("kram@example.com"));
+
+    // Left alone until a setting changes, then the good keys are kept
+    store.save(&ctx).unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), corrupt);
+    ctx.layout = KeyboardLayout::Qwertz;
+    store.save(&ctx).unwrap();
+    let saved = read(&path);
+    assert_eq!(saved["dark_mode"], true);
+    assert_eq!(saved["layout"], "qwertz");
+    assert_eq!(saved["timezone"], "UTC");
+    assert_eq!(saved["simplenote_email"], "kram@example.com");
+}
+
+#[test]
+fn newer_files_are_not_overwritten() {
+    let path = settings_file("newer");
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    let newer = format!(r#"{{ "version": {}, "dark_mode": true }}"#, VERSION + 1);
+    fs::write(&path, &newer).unwrap();
+    let mut store = SettingsStore::new(&path);
+    let mut ctx = store.load();
+    assert!(ctx.dark_mode);
+    ctx.dark_mode = false;
+    store.save(&ctx).unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), newer);
+}
+
+#[test]
+fn app_saves_settings_changes() {
+    let path = settings_file("app");
+    // Logo -> menu -> settings -> dark mode
+    let mut steps = vec![key(Key::Char('\n'))];
+    steps.extend([key(Key::Down); 3]);
+    steps.push(key(Key::Char('\n')));
+    steps.extend([key(Key::Down); 2]);
+    steps.push(key(Key::Char('\n')));
+    replay(App::with_settings(headless(), SettingsStore::new(&path)), steps);
+    assert_eq!(read(&path)["dark_mode"], true);
+
+    // The next start is dark from the logo on
+    let app = App::with_settings(headless(), SettingsStore::new(&path));
+    assert!(app.context().dark_mode);
+}
+
+#[test]
+fn hour_offsets_become_zones() {
+    for (old, zone) in [("5.5", "Asia/Kolkata"), ("-5", "Etc/GMT+5"), ("2", "Etc/GMT-2"), ("UTC", "UTC")] {
+        let path = settings_file(&format!("offset_{}", old));
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, format!(r#"{{ "version": 1, "timezone": "{}" }}"#, old)).unwrap();
+        let ctx = SettingsStore::new(&path).load();
+        assert_eq!(ctx.timezone, zone);
+        assert_eq!(ctx.tz().name(), zone);
+    }
+}